@@ -0,0 +1,23 @@
+//! Errors generated by this crate.
+
+use core::fmt::{self, Display};
+
+/// Public key is a low-order point.
+///
+/// X25519 with such a key yields an all-zero shared secret regardless of the
+/// secret key, i.e. the key agreement isn't contributory. libsodium's
+/// `crypto_box_beforenm` rejects these keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WeakPublicKey;
+
+impl Display for WeakPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("public key is a low-order point")
+    }
+}
+
+impl From<WeakPublicKey> for aead::Error {
+    fn from(_: WeakPublicKey) -> aead::Error {
+        aead::Error
+    }
+}
@@ -0,0 +1,51 @@
+//! Legacy NaCl `crypto_box` API which operates on zero-padded buffers.
+//!
+//! The original NaCl C API (and many of its ports) expects the message to be
+//! prefixed with [`ZEROBYTES`] zero bytes, and produces a ciphertext which is
+//! prefixed with [`BOXZEROBYTES`] zero bytes followed by the Poly1305 tag.
+//!
+//! See [`crypto_secretbox::legacy`] for more information.
+
+use crate::{CryptoBox, Nonce};
+use aead::{
+    consts::{U32, U8},
+    Error,
+};
+use crypto_secretbox::{
+    cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher},
+    Kdf,
+};
+
+pub use crypto_secretbox::legacy::{BOXZEROBYTES, ZEROBYTES};
+
+/// Encrypt a zero-padded message in-place, equivalent to NaCl's
+/// `crypto_box_afternm` function.
+///
+/// The first [`ZEROBYTES`] bytes of `buffer` must be zero. On success the
+/// buffer contains the zero-padded ciphertext.
+pub fn encrypt_in_place<C>(
+    crypto_box: &CryptoBox<C>,
+    nonce: &Nonce,
+    buffer: &mut [u8],
+) -> Result<(), Error>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    crypto_secretbox::legacy::encrypt_in_place(&crypto_box.secretbox, nonce, buffer)
+}
+
+/// Decrypt a zero-padded ciphertext in-place, equivalent to NaCl's
+/// `crypto_box_open_afternm` function.
+///
+/// The first [`BOXZEROBYTES`] bytes of `buffer` must be zero. On success the
+/// buffer contains the zero-padded message.
+pub fn decrypt_in_place<C>(
+    crypto_box: &CryptoBox<C>,
+    nonce: &Nonce,
+    buffer: &mut [u8],
+) -> Result<(), Error>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    crypto_secretbox::legacy::decrypt_in_place(&crypto_box.secretbox, nonce, buffer)
+}
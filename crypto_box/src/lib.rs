@@ -165,27 +165,51 @@
 //! [ECIES]: https://en.wikipedia.org/wiki/Integrated_Encryption_Scheme
 //! [`heapless::Vec`]: https://docs.rs/heapless/latest/heapless/struct.Vec.html
 
-#[cfg(feature = "seal")]
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+pub mod errors;
+pub mod legacy;
+
 mod public_key;
 mod secret_key;
+mod shared_key;
+
+#[cfg(feature = "seal")]
+mod seal;
 
-pub use crate::{public_key::PublicKey, secret_key::SecretKey};
+pub use crate::{public_key::PublicKey, secret_key::SecretKey, shared_key::SharedKey};
 pub use aead;
-pub use crypto_secretbox::Nonce;
+pub use crypto_secretbox::{nonce, padding, Nonce};
 
+#[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+pub use crypto_secretbox::container;
+
+use crate::errors::WeakPublicKey;
 use aead::{
     consts::{U0, U16, U24, U32, U8},
     generic_array::GenericArray,
-    AeadCore, AeadInPlace, Buffer, Error, KeyInit,
+    AeadCore, AeadInPlace, Buffer, Error,
 };
+use core::ops::DerefMut;
 use crypto_secretbox::{
     cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher},
+    padding::Padding,
     Kdf, SecretBox,
 };
+use curve25519_dalek::MontgomeryPoint;
+use subtle::ConstantTimeEq;
 use zeroize::Zeroizing;
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+use crate::container::{Container, ContainerAlgorithm};
+
+#[cfg(all(feature = "alloc", feature = "rand_core"))]
+use aead::rand_core::CryptoRngCore;
+
 #[cfg(feature = "chacha20")]
 use chacha20::ChaCha20Legacy as ChaCha20;
 
@@ -261,19 +285,261 @@ impl<C> CryptoBox<C> {
         C: Kdf,
     {
         let shared_secret = Zeroizing::new(secret_key.scalar * public_key.0);
+        Self::from_shared_secret(&shared_secret)
+    }
+
+    /// Create a new [`CryptoBox`] like [`CryptoBox::new`], returning
+    /// [`WeakPublicKey`] if `public_key` is a low-order point or the shared
+    /// secret is all-zero, like libsodium's `crypto_box_beforenm`.
+    ///
+    /// Key agreement with a low-order point isn't contributory: whoever
+    /// supplied it could predict the key regardless of `secret_key`.
+    pub fn try_new(public_key: &PublicKey, secret_key: &SecretKey) -> Result<Self, WeakPublicKey>
+    where
+        C: Kdf,
+    {
+        // The secret scalar is reduced modulo the group order, so it doesn't
+        // clear the cofactor and low-order points must be checked for
+        // separately.
+        public_key.validate()?;
+
+        let shared_secret = Zeroizing::new(secret_key.scalar * public_key.0);
+
+        if shared_secret.0.ct_eq(&[0u8; KEY_SIZE]).into() {
+            return Err(WeakPublicKey);
+        }
 
+        Ok(Self::from_shared_secret(&shared_secret))
+    }
+
+    /// Derive the key from the X25519 shared secret.
+    fn from_shared_secret(shared_secret: &MontgomeryPoint) -> Self
+    where
+        C: Kdf,
+    {
         // Use HChaCha20 to create a uniformly random key from the shared secret
         let key = Zeroizing::new(C::kdf(
             GenericArray::from_slice(&shared_secret.0),
             &GenericArray::default(),
         ));
 
+        SharedKey((*key).into()).into()
+    }
+
+    /// Export the precomputed [`SharedKey`] of this [`CryptoBox`], i.e. the
+    /// output of libsodium's `crypto_box_beforenm`.
+    ///
+    /// # ⚠️Warning
+    ///
+    /// The returned key is secret key material. Please treat it with the care
+    /// it deserves!
+    pub fn to_shared_key(&self) -> SharedKey {
+        SharedKey(self.secretbox.secret_key().clone())
+    }
+}
+
+impl<C> From<SharedKey> for CryptoBox<C> {
+    fn from(shared_key: SharedKey) -> CryptoBox<C> {
         Self {
-            secretbox: SecretBox::<C>::new(&*key),
+            secretbox: shared_key.0.into(),
         }
     }
 }
 
+impl<C> From<&SharedKey> for CryptoBox<C> {
+    fn from(shared_key: &SharedKey) -> CryptoBox<C> {
+        shared_key.clone().into()
+    }
+}
+
+impl<C> CryptoBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Encrypt the plaintext in `buffer[TAG_SIZE..]` in-place, writing the
+    /// Poly1305 tag into the first [`CryptoBox::TAG_SIZE`] bytes.
+    ///
+    /// See [`SecretBox::encrypt_in_place_prefixed`] for more information.
+    pub fn encrypt_in_place_prefixed(&self, nonce: &Nonce, buffer: &mut [u8]) -> Result<(), Error> {
+        self.secretbox.encrypt_in_place_prefixed(nonce, buffer)
+    }
+
+    /// Decrypt a buffer laid out as a Poly1305 tag followed by the ciphertext
+    /// in-place, returning the decrypted message, i.e. `buffer[TAG_SIZE..]`.
+    ///
+    /// See [`SecretBox::decrypt_in_place_prefixed`] for more information.
+    pub fn decrypt_in_place_prefixed<'a>(
+        &self,
+        nonce: &Nonce,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a mut [u8], Error> {
+        self.secretbox.decrypt_in_place_prefixed(nonce, buffer)
+    }
+
+    /// Check the Poly1305 tag of a ciphertext laid out as the tag followed by
+    /// the encrypted message, without decrypting it.
+    ///
+    /// See [`SecretBox::verify`] for more information.
+    pub fn verify(&self, nonce: &Nonce, ciphertext: &[u8]) -> Result<(), Error> {
+        self.secretbox.verify(nonce, ciphertext)
+    }
+
+    /// Check the Poly1305 tag of a ciphertext without decrypting it.
+    ///
+    /// See [`SecretBox::verify_detached`] for more information.
+    pub fn verify_detached(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        self.secretbox.verify_detached(nonce, ciphertext, tag)
+    }
+
+    /// Encrypt a message made up of several segments in-place, returning the
+    /// Poly1305 tag.
+    ///
+    /// See [`SecretBox::encrypt_vectored_detached`] for more information.
+    pub fn encrypt_vectored_detached<S>(&self, nonce: &Nonce, segments: &mut [S]) -> Tag
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        self.secretbox.encrypt_vectored_detached(nonce, segments)
+    }
+
+    /// Decrypt a message made up of several segments in-place, after checking
+    /// the Poly1305 tag over all of them.
+    ///
+    /// See [`SecretBox::decrypt_vectored_detached`] for more information.
+    pub fn decrypt_vectored_detached<S>(
+        &self,
+        nonce: &Nonce,
+        segments: &mut [S],
+        tag: &Tag,
+    ) -> Result<(), Error>
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        self.secretbox
+            .decrypt_vectored_detached(nonce, segments, tag)
+    }
+
+    /// Pad the plaintext in `buffer` according to the given policy, then
+    /// encrypt it in-place.
+    ///
+    /// See [`SecretBox::encrypt_padded_in_place`] for more information.
+    pub fn encrypt_padded_in_place(
+        &self,
+        nonce: &Nonce,
+        buffer: &mut dyn Buffer,
+        padding: Padding,
+    ) -> Result<(), Error> {
+        self.secretbox
+            .encrypt_padded_in_place(nonce, buffer, padding)
+    }
+
+    /// Decrypt the ciphertext in `buffer` in-place, then remove the padding
+    /// from the plaintext.
+    ///
+    /// See [`SecretBox::decrypt_padded_in_place`] for more information.
+    pub fn decrypt_padded_in_place(
+        &self,
+        nonce: &Nonce,
+        buffer: &mut dyn Buffer,
+        padding: Padding,
+    ) -> Result<(), Error> {
+        self.secretbox
+            .decrypt_padded_in_place(nonce, buffer, padding)
+    }
+
+    /// Pad the plaintext according to the given policy and encrypt it,
+    /// returning the ciphertext.
+    ///
+    /// See [`SecretBox::encrypt_padded`] for more information.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_padded(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, Error> {
+        self.secretbox.encrypt_padded(nonce, plaintext, padding)
+    }
+
+    /// Decrypt the ciphertext and remove the padding, returning the
+    /// plaintext.
+    ///
+    /// See [`SecretBox::decrypt_padded`] for more information.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_padded(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, Error> {
+        self.secretbox.decrypt_padded(nonce, ciphertext, padding)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C> CryptoBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Encrypt a batch of messages in-place, each under its own nonce.
+    ///
+    /// See [`SecretBox::encrypt_batch`] for more information.
+    pub fn encrypt_batch(&self, items: &mut [(Nonce, &mut [u8])]) -> Vec<Result<(), Error>> {
+        self.secretbox.encrypt_batch(items)
+    }
+
+    /// Verify and decrypt a batch of messages in-place, each under its own
+    /// nonce.
+    ///
+    /// See [`SecretBox::decrypt_batch`] for more information.
+    pub fn decrypt_batch(&self, items: &mut [(Nonce, &mut [u8])]) -> Vec<Result<(), Error>> {
+        self.secretbox.decrypt_batch(items)
+    }
+}
+
+#[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+impl<C> CryptoBox<C>
+where
+    C: ContainerAlgorithm
+        + Kdf
+        + KeyIvInit
+        + KeySizeUser<KeySize = U32>
+        + IvSizeUser<IvSize = U8>
+        + StreamCipher,
+{
+    /// Encrypt `plaintext` under a random nonce, returning a container which
+    /// records the algorithm and nonce.
+    ///
+    /// See [`SecretBox::encrypt_to_container`] for more information.
+    #[cfg(feature = "rand_core")]
+    pub fn encrypt_to_container(
+        &self,
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        self.secretbox.encrypt_to_container(csprng, plaintext)
+    }
+
+    /// Decrypt a container produced by [`CryptoBox::encrypt_to_container`].
+    ///
+    /// The key of a [`CryptoBox`] is derived from the shared secret using the
+    /// cipher's [`Kdf`], so only containers produced with the same cipher can
+    /// be opened. Use [`SecretKey::open_container`] to open containers
+    /// produced with any supported cipher.
+    pub fn open_container(&self, container: &[u8]) -> Result<Vec<u8>, Error> {
+        if Container::parse(container)?.algorithm() != C::ALGORITHM {
+            return Err(Error);
+        }
+
+        self.secretbox.open_container(container)
+    }
+}
+
 impl<C> AeadCore for CryptoBox<C> {
     type NonceSize = U24;
     type TagSize = U16;
@@ -326,15 +592,6 @@ where
     }
 }
 
-#[cfg(feature = "seal")]
-fn get_seal_nonce(ephemeral_pk: &PublicKey, recipient_pk: &PublicKey) -> Nonce {
-    use blake2::{Blake2b, Digest};
-    let mut hasher = Blake2b::<U24>::new();
-    hasher.update(ephemeral_pk.as_bytes());
-    hasher.update(recipient_pk.as_bytes());
-    hasher.finalize()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,6 +618,10 @@ mod tests {
         let serialized = rmp_serde::to_vec_named(&public_key).unwrap();
         let deserialized: PublicKey = rmp_serde::from_slice(&serialized).unwrap();
         assert_eq!(deserialized, public_key,);
+
+        // Low-order points are rejected
+        let serialized = bincode::serialize(&PublicKey::from([0; 32])).unwrap();
+        assert!(bincode::deserialize::<PublicKey>(&serialized).is_err());
     }
 
     #[cfg(feature = "serde")]
@@ -387,6 +648,30 @@ mod tests {
         assert_eq!(deserialized.to_bytes(), secret_key.to_bytes());
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_shared_key_serialization() {
+        use aead::rand_core::RngCore;
+
+        // Random shared key bytes
+        let mut shared_key_bytes = [0; 32];
+        let mut rng = rand::thread_rng();
+        rng.fill_bytes(&mut shared_key_bytes);
+
+        // Create shared key
+        let shared_key = SharedKey::from(shared_key_bytes);
+
+        // Round-trip serialize with bincode
+        let serialized = bincode::serialize(&shared_key).unwrap();
+        let deserialized: SharedKey = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, shared_key);
+
+        // Round-trip serialize with rmp (msgpack)
+        let serialized = rmp_serde::to_vec_named(&shared_key).unwrap();
+        let deserialized: SharedKey = rmp_serde::from_slice(&serialized).unwrap();
+        assert_eq!(deserialized, shared_key);
+    }
+
     #[test]
     fn test_public_key_from_slice() {
         let array = [0; 40];
@@ -1,12 +1,21 @@
-use crate::{SecretKey, KEY_SIZE};
+use crate::{errors::WeakPublicKey, SecretKey, KEY_SIZE};
 use core::{array::TryFromSliceError, cmp::Ordering};
-use curve25519_dalek::MontgomeryPoint;
-
-#[cfg(feature = "seal")]
+use curve25519_dalek::{MontgomeryPoint, Scalar};
+
+#[cfg(all(
+    feature = "seal",
+    feature = "alloc",
+    feature = "rand_core",
+    feature = "salsa20"
+))]
 use {
-    crate::{get_seal_nonce, SalsaBox, TAG_SIZE},
-    aead::{rand_core::CryptoRngCore, Aead},
+    crate::{
+        padding::{self, Padding},
+        SalsaBox,
+    },
+    aead::rand_core::CryptoRngCore,
     alloc::vec::Vec,
+    zeroize::Zeroizing,
 };
 
 #[cfg(feature = "serde")]
@@ -24,6 +33,16 @@ impl PublicKey {
         PublicKey(MontgomeryPoint(bytes))
     }
 
+    /// Initialize [`PublicKey`] from a byte array, returning
+    /// [`WeakPublicKey`] if it's a low-order point.
+    ///
+    /// See [`PublicKey::validate`] for more information.
+    pub fn try_from_bytes(bytes: [u8; KEY_SIZE]) -> Result<Self, WeakPublicKey> {
+        let public_key = Self::from_bytes(bytes);
+        public_key.validate()?;
+        Ok(public_key)
+    }
+
     /// Initialize [`PublicKey`] from a byte slice.
     ///
     /// Returns [`TryFromSliceError`] if the slice length is not exactly equal
@@ -32,6 +51,21 @@ impl PublicKey {
         slice.try_into().map(Self::from_bytes)
     }
 
+    /// Check that this public key isn't a low-order point.
+    ///
+    /// Key agreement with a low-order point isn't contributory, so
+    /// [`CryptoBox::try_new`] rejects such keys too.
+    ///
+    /// [`CryptoBox::try_new`]: crate::CryptoBox::try_new
+    pub fn validate(&self) -> Result<(), WeakPublicKey> {
+        // Clearing the cofactor maps exactly the low-order points to zero
+        if (self.0 * Scalar::from(8u8)).0 == [0u8; KEY_SIZE] {
+            Err(WeakPublicKey)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Borrow the public key as bytes.
     pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
         self.0.as_bytes()
@@ -46,24 +80,48 @@ impl PublicKey {
     ///
     /// Sealed boxes are designed to anonymously send messages to a recipient given their public key.
     ///
+    /// Returns [`aead::Error`] if this public key is a low-order point.
+    ///
+    /// This is [`CryptoBox::seal`] instantiated with [`SalsaBox`].
+    ///
     /// [libsodium "sealed boxes"]: https://doc.libsodium.org/public-key_cryptography/sealed_boxes
-    #[cfg(feature = "seal")]
+    /// [`CryptoBox::seal`]: crate::CryptoBox::seal
+    #[cfg(all(
+        feature = "seal",
+        feature = "alloc",
+        feature = "rand_core",
+        feature = "salsa20"
+    ))]
     pub fn seal(
         &self,
         csprng: &mut impl CryptoRngCore,
         plaintext: &[u8],
     ) -> Result<Vec<u8>, aead::Error> {
-        let mut out = Vec::with_capacity(KEY_SIZE + TAG_SIZE + plaintext.len());
-        let ephemeral_sk = SecretKey::generate(csprng);
-        let ephemeral_pk = ephemeral_sk.public_key();
-        out.extend_from_slice(ephemeral_pk.as_bytes());
-
-        let nonce = get_seal_nonce(&ephemeral_pk, self);
-        let salsabox = SalsaBox::new(self, &ephemeral_sk);
-        let encrypted = salsabox.encrypt(&nonce, plaintext)?;
-        out.extend_from_slice(&encrypted);
+        SalsaBox::seal(self, csprng, plaintext)
+    }
 
-        Ok(out)
+    /// Pad the plaintext according to the given policy, then seal it with
+    /// [`PublicKey::seal`].
+    ///
+    /// This hides the length of the plaintext within the padding policy's
+    /// granularity. Use [`SecretKey::unseal_padded`] to open the result.
+    #[cfg(all(
+        feature = "seal",
+        feature = "alloc",
+        feature = "rand_core",
+        feature = "salsa20"
+    ))]
+    pub fn seal_padded(
+        &self,
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, aead::Error> {
+        let padded_len = padding.padded_len(plaintext.len()).ok_or(aead::Error)?;
+        let mut padded = Zeroizing::new(Vec::with_capacity(padded_len));
+        padded.extend_from_slice(plaintext);
+        padding::pad(&mut *padded, padding)?;
+        self.seal(csprng, &padded)
     }
 }
 
@@ -129,6 +187,6 @@ impl<'de> Deserialize<'de> for PublicKey {
     {
         let mut bytes = [0u8; KEY_SIZE];
         serdect::array::deserialize_hex_or_bin(&mut bytes, deserializer)?;
-        Ok(PublicKey::from(bytes)) // TODO(tarcieri): validate key
+        PublicKey::try_from_bytes(bytes).map_err(de::Error::custom)
     }
 }
@@ -0,0 +1,170 @@
+//! Sealed boxes, i.e. libsodium's `crypto_box_seal` and
+//! `crypto_box_curve25519xchacha20poly1305_seal`.
+//!
+//! A sealed box is encrypted under an ephemeral keypair which is discarded
+//! once the message has been encrypted, so it can be opened by the recipient
+//! without revealing who sent it. It's laid out as:
+//!
+//! ```text
+//! ephemeral public key (32) || Poly1305 tag (16) || ciphertext
+//! ```
+//!
+//! where the nonce is the BLAKE2b-192 hash of the ephemeral public key
+//! followed by the recipient's public key.
+
+use crate::{CryptoBox, Nonce, PublicKey, SecretKey, Tag, KEY_SIZE, SEALBYTES};
+use aead::{
+    consts::{U24, U32, U8},
+    AeadInPlace, Buffer, Error,
+};
+use blake2::{Blake2b, Digest};
+use crypto_secretbox::{
+    cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher},
+    Kdf,
+};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "rand_core")]
+use aead::rand_core::CryptoRngCore;
+
+impl<C> CryptoBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Seal `plaintext` for the owner of `public_key` under a freshly
+    /// generated ephemeral keypair.
+    ///
+    /// Returns [`Error`] if `public_key` is a low-order point.
+    #[cfg(all(feature = "alloc", feature = "rand_core"))]
+    pub fn seal(
+        public_key: &PublicKey,
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::with_capacity(SEALBYTES + plaintext.len());
+        buffer.extend_from_slice(plaintext);
+        Self::seal_in_place(public_key, csprng, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Open a sealed box using the recipient's `secret_key`.
+    ///
+    /// Returns [`Error`] if decryption fails, including if the ephemeral
+    /// public key is a low-order point.
+    #[cfg(feature = "alloc")]
+    pub fn unseal(secret_key: &SecretKey, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buffer = ciphertext.to_vec();
+        Self::unseal_in_place(secret_key, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Seal the plaintext in `buffer` in-place for the owner of
+    /// `public_key`, prepending the ephemeral public key and tag.
+    ///
+    /// `buffer` needs [`SEALBYTES`] of spare capacity. Returns [`Error`] if
+    /// it doesn't or `public_key` is a low-order point, in which case
+    /// `buffer` is left unmodified.
+    #[cfg(feature = "rand_core")]
+    pub fn seal_in_place(
+        public_key: &PublicKey,
+        csprng: &mut impl CryptoRngCore,
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), Error> {
+        let len = buffer.len();
+        buffer.extend_from_slice(&[0u8; SEALBYTES])?;
+        buffer.as_mut().copy_within(..len, SEALBYTES);
+
+        let (header, plaintext) = buffer.as_mut().split_at_mut(SEALBYTES);
+        let (ephemeral_pk, tag) = header.split_at_mut(KEY_SIZE);
+        let result = Self::seal_in_place_detached(
+            public_key,
+            csprng,
+            plaintext,
+            ephemeral_pk.try_into().unwrap(),
+            Tag::from_mut_slice(tag),
+        );
+
+        if result.is_err() {
+            buffer.as_mut().copy_within(SEALBYTES.., 0);
+            buffer.truncate(len);
+        }
+
+        result
+    }
+
+    /// Open the sealed box in `buffer` in-place using the recipient's
+    /// `secret_key`, leaving the plaintext.
+    ///
+    /// Returns [`Error`] if decryption fails, in which case `buffer` is left
+    /// unmodified.
+    pub fn unseal_in_place(secret_key: &SecretKey, buffer: &mut dyn Buffer) -> Result<(), Error> {
+        if buffer.len() < SEALBYTES {
+            return Err(Error);
+        }
+
+        let (header, ciphertext) = buffer.as_mut().split_at_mut(SEALBYTES);
+        let (ephemeral_pk, tag) = header.split_at(KEY_SIZE);
+        Self::unseal_in_place_detached(
+            secret_key,
+            ephemeral_pk.try_into().unwrap(),
+            Tag::from_slice(tag),
+            ciphertext,
+        )?;
+
+        let len = buffer.len() - SEALBYTES;
+        buffer.as_mut().copy_within(SEALBYTES.., 0);
+        buffer.truncate(len);
+        Ok(())
+    }
+
+    /// Seal the plaintext in `buffer` in-place for the owner of
+    /// `public_key`, writing the ephemeral public key and tag into
+    /// `ephemeral_pk` and `tag`.
+    ///
+    /// Returns [`Error`] if `public_key` is a low-order point.
+    #[cfg(feature = "rand_core")]
+    pub fn seal_in_place_detached(
+        public_key: &PublicKey,
+        csprng: &mut impl CryptoRngCore,
+        buffer: &mut [u8],
+        ephemeral_pk: &mut [u8; KEY_SIZE],
+        tag: &mut Tag,
+    ) -> Result<(), Error> {
+        let ephemeral_sk = SecretKey::generate(csprng);
+        let ephemeral_public_key = ephemeral_sk.public_key();
+
+        let nonce = get_seal_nonce(&ephemeral_public_key, public_key);
+        *tag = Self::try_new(public_key, &ephemeral_sk)?
+            .encrypt_in_place_detached(&nonce, b"", buffer)?;
+        *ephemeral_pk = ephemeral_public_key.to_bytes();
+        Ok(())
+    }
+
+    /// Open a detached sealed box in-place using the recipient's
+    /// `secret_key`, given the ephemeral public key and tag.
+    ///
+    /// Returns [`Error`] if decryption fails, including if `ephemeral_pk` is
+    /// a low-order point.
+    pub fn unseal_in_place_detached(
+        secret_key: &SecretKey,
+        ephemeral_pk: &[u8; KEY_SIZE],
+        tag: &Tag,
+        buffer: &mut [u8],
+    ) -> Result<(), Error> {
+        let ephemeral_pk = PublicKey::from(*ephemeral_pk);
+        let nonce = get_seal_nonce(&ephemeral_pk, &secret_key.public_key());
+        Self::try_new(&ephemeral_pk, secret_key)?
+            .decrypt_in_place_detached(&nonce, b"", buffer, tag)
+    }
+}
+
+/// Derive the nonce of a sealed box from the ephemeral and recipient public
+/// keys.
+fn get_seal_nonce(ephemeral_pk: &PublicKey, recipient_pk: &PublicKey) -> Nonce {
+    let mut hasher = Blake2b::<U24>::new();
+    hasher.update(ephemeral_pk.as_bytes());
+    hasher.update(recipient_pk.as_bytes());
+    hasher.finalize()
+}
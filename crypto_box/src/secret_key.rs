@@ -7,16 +7,22 @@ use curve25519_dalek::{
     scalar::{clamp_integer, Scalar},
     MontgomeryPoint,
 };
+use sha2::{digest::generic_array::GenericArray, Digest, Sha512};
 use subtle::ConstantTimeEq;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 #[cfg(feature = "rand_core")]
 use aead::rand_core::CryptoRngCore;
 
-#[cfg(feature = "seal")]
+#[cfg(all(feature = "seal", feature = "alloc", feature = "salsa20"))]
+use crate::{
+    padding::{self, Padding},
+    SalsaBox,
+};
+
+#[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
 use {
-    crate::{get_seal_nonce, SalsaBox},
-    aead::Aead,
+    crate::container::{Algorithm, Container},
     alloc::vec::Vec,
 };
 
@@ -46,6 +52,23 @@ impl SecretKey {
         slice.try_into().map(Self::from_bytes)
     }
 
+    /// Derive a [`SecretKey`] deterministically from a seed.
+    ///
+    /// The secret key is the first half of the SHA-512 hash of the seed, as
+    /// in libsodium's `crypto_box_seed_keypair`, so the same seed yields the
+    /// same keypair as libsodium.
+    ///
+    /// The seed must be uniformly random and kept secret, just like a secret
+    /// key.
+    pub fn from_seed(seed: &[u8; KEY_SIZE]) -> Self {
+        let mut hash = Zeroizing::new([0u8; 64]);
+        Sha512::new()
+            .chain_update(seed)
+            .finalize_into(GenericArray::from_mut_slice(&mut *hash));
+
+        Self::from_slice(&hash[..KEY_SIZE]).unwrap()
+    }
+
     /// Generate a random [`SecretKey`].
     #[cfg(feature = "rand_core")]
     pub fn generate(csprng: &mut impl CryptoRngCore) -> Self {
@@ -98,18 +121,62 @@ impl SecretKey {
     ///
     /// Sealed boxes are designed to anonymously send messages to a recipient given their public key.
     ///
+    /// Returns [`aead::Error`] if decryption fails, including if the
+    /// ephemeral public key is a low-order point.
+    ///
+    /// This is [`CryptoBox::unseal`] instantiated with [`SalsaBox`].
+    ///
     /// [libsodium "sealed boxes"]: https://doc.libsodium.org/public-key_cryptography/sealed_boxes
-    #[cfg(feature = "seal")]
+    /// [`CryptoBox::unseal`]: crate::CryptoBox::unseal
+    #[cfg(all(feature = "seal", feature = "alloc", feature = "salsa20"))]
     pub fn unseal(&self, ciphertext: &[u8]) -> Result<Vec<u8>, aead::Error> {
-        if ciphertext.len() <= KEY_SIZE {
-            return Err(aead::Error);
+        SalsaBox::unseal(self, ciphertext)
+    }
+
+    /// Open a sealed box created with [`PublicKey::seal_padded`], removing
+    /// the padding from the plaintext.
+    #[cfg(all(feature = "seal", feature = "alloc", feature = "salsa20"))]
+    pub fn unseal_padded(
+        &self,
+        ciphertext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, aead::Error> {
+        let mut plaintext = self.unseal(ciphertext)?;
+
+        if let Err(e) = padding::unpad(&mut plaintext, padding) {
+            plaintext.as_mut_slice().zeroize();
+            return Err(e);
         }
 
-        let ephemeral_sk: [u8; KEY_SIZE] = ciphertext[..KEY_SIZE].try_into().unwrap();
-        let ephemeral_pk = ephemeral_sk.into();
-        let nonce = get_seal_nonce(&ephemeral_pk, &self.public_key());
-        let salsabox = SalsaBox::new(&ephemeral_pk, self);
-        salsabox.decrypt(&nonce, &ciphertext[KEY_SIZE..])
+        Ok(plaintext)
+    }
+
+    /// Open a container produced by [`CryptoBox::encrypt_to_container`]
+    /// under the box formed by this key and `public_key`, using the cipher
+    /// recorded in the container.
+    ///
+    /// Returns [`aead::Error`] if the container can't be parsed, its cipher
+    /// isn't enabled, or decryption fails.
+    ///
+    /// [`CryptoBox::encrypt_to_container`]: crate::CryptoBox::encrypt_to_container
+    #[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+    pub fn open_container(
+        &self,
+        public_key: &PublicKey,
+        container: &[u8],
+    ) -> Result<Vec<u8>, aead::Error> {
+        match Container::parse(container)?.algorithm() {
+            #[cfg(feature = "salsa20")]
+            Algorithm::XSalsa20Poly1305 => {
+                crate::SalsaBox::new(public_key, self).open_container(container)
+            }
+            #[cfg(feature = "chacha20")]
+            Algorithm::XChaCha20Poly1305 => {
+                crate::ChaChaBox::new(public_key, self).open_container(container)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(aead::Error),
+        }
     }
 }
 
@@ -0,0 +1,95 @@
+use crate::KEY_SIZE;
+use core::{
+    array::TryFromSliceError,
+    fmt::{self, Debug},
+};
+
+#[cfg(feature = "serde")]
+use serdect::serde::{de, ser, Deserialize, Serialize};
+
+/// A precomputed `crypto_box` shared key.
+///
+/// This is the output of libsodium's `crypto_box_beforenm`, i.e. the X25519
+/// shared secret passed through the cipher's [`Kdf`], and can be used with
+/// `crypto_box_afternm` or converted back into a [`CryptoBox`] without
+/// repeating the key agreement.
+///
+/// The key depends on the cipher it was computed for: a key exported from a
+/// [`SalsaBox`] must not be used to build a `ChaChaBox` or vice versa.
+///
+/// The key is zeroized when dropped, and compared in constant time.
+///
+/// This type can be serialized if the `serde` feature is enabled.
+///
+/// [`Kdf`]: crypto_secretbox::Kdf
+/// [`CryptoBox`]: crate::CryptoBox
+/// [`SalsaBox`]: crate::SalsaBox
+#[derive(Clone, Eq, PartialEq)]
+pub struct SharedKey(pub(crate) crypto_secretbox::SecretKey);
+
+impl SharedKey {
+    /// Initialize [`SharedKey`] from a byte array.
+    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Initialize [`SharedKey`] from a byte slice.
+    ///
+    /// Returns [`TryFromSliceError`] if the slice length is not exactly equal
+    /// to [`KEY_SIZE`].
+    pub fn from_slice(slice: &[u8]) -> Result<Self, TryFromSliceError> {
+        slice.try_into().map(Self::from_bytes)
+    }
+
+    /// Borrow the shared key as bytes.
+    ///
+    /// # ⚠️Warning
+    ///
+    /// The returned bytes are secret key material. Please treat them with
+    /// the care they deserve!
+    pub fn as_bytes(&self) -> &[u8; KEY_SIZE] {
+        self.0.as_bytes()
+    }
+}
+
+impl Debug for SharedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedKey").finish_non_exhaustive()
+    }
+}
+
+impl From<[u8; KEY_SIZE]> for SharedKey {
+    fn from(bytes: [u8; KEY_SIZE]) -> SharedKey {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for SharedKey {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, TryFromSliceError> {
+        Self::from_slice(slice)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SharedKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serdect::array::serialize_hex_upper_or_bin(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SharedKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut bytes = zeroize::Zeroizing::new([0u8; KEY_SIZE]);
+        serdect::array::deserialize_hex_or_bin(&mut *bytes, deserializer)?;
+        Ok(SharedKey::from(*bytes))
+    }
+}
@@ -11,7 +11,9 @@
 
 use crypto_box::{
     aead::{generic_array::GenericArray, Aead, AeadInPlace, OsRng},
-    PublicKey, SecretKey,
+    errors, legacy,
+    nonce::{self, NonceSequence},
+    PublicKey, SecretKey, SharedKey,
 };
 use curve25519_dalek::EdwardsPoint;
 use hex_literal::hex;
@@ -58,6 +60,29 @@ fn secret_and_public_keys() {
     assert_eq!(secret_key.public_key().as_bytes(), &ALICE_PUBLIC_KEY);
 }
 
+#[test]
+fn secret_key_from_seed() {
+    // Generated with `crypto_box_seed_keypair`
+    const SEED_VECTORS: [([u8; 32], [u8; 32], [u8; 32]); 2] = [
+        (
+            hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+            hex!("5046adc1dba838867b2bbbfdd0c3423e58b57970b5267a90f57960924a87f196"),
+            hex!("5bf55c73b82ebe22be80f3430667af570fae2556a6415e6b30d4065300aa947d"),
+        ),
+        (
+            hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"),
+            hex!("3d94eea49c580aef816935762be049559d6d1440dede12e6a125f1841fff8e6f"),
+            hex!("4701d08488451f545a409fb58ae3e58581ca40ac3f7f114698cd71deac73ca01"),
+        ),
+    ];
+
+    for (seed, secret_key, public_key) in SEED_VECTORS {
+        let sk = SecretKey::from_seed(&seed);
+        assert_eq!(sk.to_bytes(), secret_key);
+        assert_eq!(sk.public_key().as_bytes(), &public_key);
+    }
+}
+
 #[test]
 fn edwards_to_montgomery() {
     let secret_key = SecretKey::from(ALICE_SECRET_KEY);
@@ -68,8 +93,191 @@ fn edwards_to_montgomery() {
     assert_eq!(secret_key, SecretKey::from(scalar));
 }
 
+/// Low-order points, including non-canonical encodings.
+const LOW_ORDER_POINTS: [[u8; 32]; 7] = [
+    hex!("0000000000000000000000000000000000000000000000000000000000000000"),
+    hex!("0100000000000000000000000000000000000000000000000000000000000000"),
+    hex!("e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800"),
+    hex!("5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157"),
+    hex!("ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f"),
+    hex!("edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f"),
+    hex!("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f"),
+];
+
+#[test]
+fn validate_public_key() {
+    assert!(PublicKey::try_from_bytes(ALICE_PUBLIC_KEY).is_ok());
+    assert!(PublicKey::try_from_bytes(BOB_PUBLIC_KEY).is_ok());
+
+    for point in LOW_ORDER_POINTS {
+        assert_eq!(PublicKey::try_from_bytes(point), Err(errors::WeakPublicKey));
+    }
+}
+
 macro_rules! impl_tests {
-    ($box:ty, $plaintext:expr, $ciphertext:expr) => {
+    ($box:ty, $plaintext:expr, $ciphertext:expr, $shared_key:expr) => {
+        #[cfg(feature = "seal")]
+        #[test]
+        fn seal_in_place() {
+            let mut buffer = $plaintext.to_vec();
+            <$box>::seal_in_place(&PublicKey::from(BOB_PUBLIC_KEY), &mut OsRng, &mut buffer)
+                .unwrap();
+            assert_eq!(buffer.len(), crypto_box::SEALBYTES + $plaintext.len());
+
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            <$box>::unseal_in_place(&secret_key, &mut buffer).unwrap();
+            assert_eq!(buffer, $plaintext);
+
+            let low_order_key = PublicKey::from(LOW_ORDER_POINTS[0]);
+            assert!(<$box>::seal_in_place(&low_order_key, &mut OsRng, &mut buffer).is_err());
+            assert_eq!(buffer, $plaintext);
+        }
+
+        #[cfg(all(feature = "seal", feature = "heapless"))]
+        #[test]
+        fn seal_in_place_heapless() {
+            use crypto_box::aead::heapless::Vec;
+
+            let mut buffer = Vec::<u8, 256>::from_slice($plaintext).unwrap();
+            <$box>::seal_in_place(&PublicKey::from(BOB_PUBLIC_KEY), &mut OsRng, &mut buffer)
+                .unwrap();
+
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            <$box>::unseal_in_place(&secret_key, &mut buffer).unwrap();
+            assert_eq!(buffer, $plaintext);
+
+            // Insufficient capacity
+            let mut buffer = Vec::<u8, 160>::from_slice($plaintext).unwrap();
+            assert!(<$box>::seal_in_place(
+                &PublicKey::from(BOB_PUBLIC_KEY),
+                &mut OsRng,
+                &mut buffer
+            )
+            .is_err());
+            assert_eq!(buffer, $plaintext);
+        }
+
+        #[cfg(feature = "seal")]
+        #[test]
+        fn seal_in_place_detached() {
+            let mut buffer = $plaintext.to_vec();
+            let mut ephemeral_pk = [0u8; 32];
+            let mut tag = Default::default();
+            <$box>::seal_in_place_detached(
+                &PublicKey::from(BOB_PUBLIC_KEY),
+                &mut OsRng,
+                &mut buffer,
+                &mut ephemeral_pk,
+                &mut tag,
+            )
+            .unwrap();
+
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let mut sealed = ephemeral_pk.to_vec();
+            sealed.extend_from_slice(&tag);
+            sealed.extend_from_slice(&buffer);
+
+            <$box>::unseal_in_place_detached(&secret_key, &ephemeral_pk, &tag, &mut buffer)
+                .unwrap();
+            assert_eq!(buffer, $plaintext);
+
+            #[cfg(feature = "alloc")]
+            assert_eq!(<$box>::unseal(&secret_key, &sealed).unwrap(), $plaintext);
+        }
+
+        #[cfg(all(feature = "seal", feature = "alloc"))]
+        #[test]
+        fn seal() {
+            let sealed =
+                <$box>::seal(&PublicKey::from(BOB_PUBLIC_KEY), &mut OsRng, $plaintext).unwrap();
+            assert_eq!(sealed.len(), crypto_box::SEALBYTES + $plaintext.len());
+
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            assert_eq!(<$box>::unseal(&secret_key, &sealed).unwrap(), $plaintext);
+
+            let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+            assert!(<$box>::unseal(&secret_key, &sealed).is_err());
+        }
+
+        #[test]
+        fn try_new() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+
+            let shared_key = <$box>::try_new(&public_key, &secret_key)
+                .unwrap()
+                .to_shared_key();
+            assert_eq!(shared_key.as_bytes(), &$shared_key);
+
+            for point in LOW_ORDER_POINTS {
+                let public_key = PublicKey::from(point);
+                assert!(<$box>::try_new(&public_key, &secret_key).is_err());
+            }
+        }
+
+        #[test]
+        fn shared_key() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+
+            let shared_key = <$box>::new(&public_key, &secret_key).to_shared_key();
+            assert_eq!(shared_key.as_bytes(), &$shared_key);
+
+            // Ensure `Debug` impl on `SharedKey` is covered in tests
+            dbg!(&shared_key);
+
+            let nonce = GenericArray::from_slice(NONCE);
+            let ciphertext = <$box>::from(SharedKey::from($shared_key))
+                .encrypt(nonce, $plaintext)
+                .unwrap();
+            assert_eq!($ciphertext, &ciphertext[..]);
+        }
+
+        #[test]
+        fn generate_nonce() {
+            let alice_box = <$box>::new(
+                &PublicKey::from(BOB_PUBLIC_KEY),
+                &SecretKey::from(ALICE_SECRET_KEY),
+            );
+            let bob_box = <$box>::new(
+                &PublicKey::from(ALICE_PUBLIC_KEY),
+                &SecretKey::from(BOB_SECRET_KEY),
+            );
+
+            let nonce = nonce::generate(&mut OsRng);
+            assert_ne!(nonce, nonce::generate(&mut OsRng));
+
+            let ciphertext = alice_box.encrypt(&nonce, $plaintext).unwrap();
+            let plaintext = bob_box.decrypt(&nonce, &ciphertext[..]).unwrap();
+            assert_eq!($plaintext, &plaintext[..]);
+        }
+
+        #[test]
+        fn nonce_sequence() {
+            let alice_box = <$box>::new(
+                &PublicKey::from(BOB_PUBLIC_KEY),
+                &SecretKey::from(ALICE_SECRET_KEY),
+            );
+            let bob_box = <$box>::new(
+                &PublicKey::from(ALICE_PUBLIC_KEY),
+                &SecretKey::from(BOB_SECRET_KEY),
+            );
+
+            let mut sequence = NonceSequence::generate(&mut OsRng);
+            let first = sequence.next_nonce().unwrap();
+            let second = sequence.next_nonce().unwrap();
+            assert_ne!(first, second);
+
+            for nonce in [first, second] {
+                let ciphertext = alice_box.encrypt(&nonce, $plaintext).unwrap();
+                let plaintext = bob_box.decrypt(&nonce, &ciphertext[..]).unwrap();
+                assert_eq!($plaintext, &plaintext[..]);
+            }
+
+            let ciphertext = alice_box.encrypt(&first, $plaintext).unwrap();
+            assert!(bob_box.decrypt(&second, &ciphertext[..]).is_err());
+        }
+
         #[test]
         fn encrypt() {
             let secret_key = SecretKey::from(ALICE_SECRET_KEY);
@@ -99,6 +307,74 @@ macro_rules! impl_tests {
             assert_eq!(expected_ciphertext, &buffer[..]);
         }
 
+        #[test]
+        fn encrypt_in_place_prefixed() {
+            let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+            let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = vec![0u8; 16];
+            buffer.extend_from_slice($plaintext);
+
+            <$box>::new(&public_key, &secret_key)
+                .encrypt_in_place_prefixed(nonce, &mut buffer)
+                .unwrap();
+
+            assert_eq!($ciphertext, &buffer[..]);
+        }
+
+        #[test]
+        fn encrypt_vectored() {
+            let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+            let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = $plaintext.to_vec();
+
+            let tag = {
+                let (a, b) = buffer.split_at_mut(33);
+                <$box>::new(&public_key, &secret_key).encrypt_vectored_detached(nonce, &mut [a, b])
+            };
+
+            assert_eq!(&$ciphertext[..16], tag.as_slice());
+            assert_eq!(&$ciphertext[16..], &buffer[..]);
+        }
+
+        #[test]
+        fn encrypt_batch() {
+            let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+            let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffers = vec![vec![0u8; 16]; 2];
+            for buffer in &mut buffers {
+                buffer.extend_from_slice($plaintext);
+            }
+
+            let mut items: Vec<_> = buffers
+                .iter_mut()
+                .map(|buffer| (*nonce, buffer.as_mut_slice()))
+                .collect();
+            let results = <$box>::new(&public_key, &secret_key).encrypt_batch(&mut items);
+
+            assert!(results.iter().all(Result::is_ok));
+            assert_eq!($ciphertext, &buffers[0][..]);
+            assert_eq!($ciphertext, &buffers[1][..]);
+        }
+
+        #[test]
+        fn legacy_encrypt_in_place() {
+            let secret_key = SecretKey::from(ALICE_SECRET_KEY);
+            let public_key = PublicKey::from(BOB_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = vec![0u8; legacy::ZEROBYTES];
+            buffer.extend_from_slice($plaintext);
+
+            legacy::encrypt_in_place(&<$box>::new(&public_key, &secret_key), nonce, &mut buffer)
+                .unwrap();
+
+            let (padding, ciphertext) = buffer.split_at(legacy::BOXZEROBYTES);
+            assert_eq!(padding, [0u8; legacy::BOXZEROBYTES]);
+            assert_eq!($ciphertext, ciphertext);
+        }
+
         #[test]
         fn decrypt() {
             let secret_key = SecretKey::from(BOB_SECRET_KEY);
@@ -126,6 +402,120 @@ macro_rules! impl_tests {
 
             assert_eq!($plaintext, &buffer[..]);
         }
+
+        #[test]
+        fn decrypt_in_place_prefixed() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = $ciphertext.to_vec();
+
+            let plaintext = <$box>::new(&public_key, &secret_key)
+                .decrypt_in_place_prefixed(nonce, &mut buffer)
+                .unwrap();
+
+            assert_eq!($plaintext, plaintext);
+        }
+
+        #[test]
+        fn decrypt_vectored() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let tag = GenericArray::from_slice(&$ciphertext[..16]);
+            let mut buffer = $ciphertext[16..].to_vec();
+
+            {
+                let (a, b) = buffer.split_at_mut(33);
+                <$box>::new(&public_key, &secret_key)
+                    .decrypt_vectored_detached(nonce, &mut [a, b], tag)
+                    .unwrap();
+            }
+
+            assert_eq!($plaintext, &buffer[..]);
+        }
+
+        #[test]
+        fn verify() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cryptobox = <$box>::new(&public_key, &secret_key);
+            assert!(cryptobox.verify(nonce, $ciphertext).is_ok());
+
+            let (tag, ciphertext) = $ciphertext.split_at(16);
+            assert!(cryptobox
+                .verify_detached(nonce, ciphertext, GenericArray::from_slice(tag))
+                .is_ok());
+
+            let mut modified = $ciphertext.to_vec();
+            modified[20] ^= 0x01;
+            assert!(cryptobox.verify(nonce, &modified).is_err());
+        }
+
+        #[test]
+        fn decrypt_batch() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffers = vec![$ciphertext.to_vec(); 2];
+            buffers[0][0] ^= 0xaa;
+
+            let mut items: Vec<_> = buffers
+                .iter_mut()
+                .map(|buffer| (*nonce, buffer.as_mut_slice()))
+                .collect();
+            let results = <$box>::new(&public_key, &secret_key).decrypt_batch(&mut items);
+
+            assert!(results[0].is_err());
+            assert!(results[1].is_ok());
+            assert_eq!($plaintext, &buffers[1][16..]);
+        }
+
+        #[test]
+        fn padded() {
+            use crypto_box::padding::Padding;
+
+            let alice_box = <$box>::new(
+                &PublicKey::from(BOB_PUBLIC_KEY),
+                &SecretKey::from(ALICE_SECRET_KEY),
+            );
+            let bob_box = <$box>::new(
+                &PublicKey::from(ALICE_PUBLIC_KEY),
+                &SecretKey::from(BOB_SECRET_KEY),
+            );
+            let nonce = GenericArray::from_slice(NONCE);
+
+            let ciphertext = alice_box
+                .encrypt_padded(nonce, $plaintext, Padding::Padme)
+                .unwrap();
+            assert_eq!(
+                ciphertext.len(),
+                16 + Padding::Padme.padded_len($plaintext.len()).unwrap()
+            );
+            assert_eq!(
+                bob_box
+                    .decrypt_padded(nonce, &ciphertext, Padding::Padme)
+                    .unwrap(),
+                $plaintext
+            );
+        }
+
+        #[test]
+        fn legacy_decrypt_in_place() {
+            let secret_key = SecretKey::from(BOB_SECRET_KEY);
+            let public_key = PublicKey::from(ALICE_PUBLIC_KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = vec![0u8; legacy::BOXZEROBYTES];
+            buffer.extend_from_slice($ciphertext);
+
+            legacy::decrypt_in_place(&<$box>::new(&public_key, &secret_key), nonce, &mut buffer)
+                .unwrap();
+
+            let (padding, plaintext) = buffer.split_at(legacy::ZEROBYTES);
+            assert_eq!(padding, [0u8; legacy::ZEROBYTES]);
+            assert_eq!($plaintext, plaintext);
+        }
     };
 }
 
@@ -146,7 +536,10 @@ mod xsalsa20poly1305 {
         0x8, 0x8, 0xdb, 0xf0, 0xf9, 0xbd, 0x30, 0xf6, 0x3b, 0x68, 0xd0, 0x26,
     ];
 
-    impl_tests!(SalsaBox, PLAINTEXT, CIPHERTEXT);
+    const SHARED_KEY: [u8; 32] =
+        hex!("9083c3c18b3a297fd1228471f25858561423cad12de72bab2cb429a9a63b1952");
+
+    impl_tests!(SalsaBox, PLAINTEXT, CIPHERTEXT, SHARED_KEY);
 }
 
 #[cfg(feature = "chacha20")]
@@ -160,10 +553,13 @@ mod xchacha20poly1305 {
         "c4a684bcfeadfdcd28930b2dbe597f4716a658ccfca5b44049e06c"
     );
 
-    impl_tests!(ChaChaBox, PLAINTEXT, CIPHERTEXT);
+    const SHARED_KEY: [u8; 32] =
+        hex!("1a264334c607cf699fe93a6911d5058f20fa96f4255bc76793dde25082f45ad8");
+
+    impl_tests!(ChaChaBox, PLAINTEXT, CIPHERTEXT, SHARED_KEY);
 }
 
-#[cfg(feature = "seal")]
+#[cfg(all(feature = "seal", feature = "alloc", feature = "salsa20"))]
 #[test]
 fn seal() {
     const SEAL_SECRET_KEY: [u8; 32] = [
@@ -197,4 +593,103 @@ fn seal() {
     let sk = SecretKey::from(SEAL_SECRET_KEY);
     assert_eq!(SEAL_PLAINTEXT, sk.unseal(&encrypted).unwrap());
     assert_eq!(SEAL_PLAINTEXT, sk.unseal(SEAL_CIPHERTEXT).unwrap());
+
+    for point in LOW_ORDER_POINTS {
+        assert!(PublicKey::from(point)
+            .seal(&mut OsRng, SEAL_PLAINTEXT)
+            .is_err());
+
+        let mut ciphertext = SEAL_CIPHERTEXT.to_vec();
+        ciphertext[..32].copy_from_slice(&point);
+        assert!(sk.unseal(&ciphertext).is_err());
+    }
+}
+
+#[cfg(all(feature = "seal", feature = "alloc", feature = "chacha20"))]
+#[test]
+fn seal_xchacha20poly1305() {
+    use crypto_box::ChaChaBox;
+
+    // Generated with `crypto_box_curve25519xchacha20poly1305_seal`
+    const SEAL_CIPHERTEXT: &[u8] = &hex!(
+        "3db0d8ef683ea420cac99902f10ff9e834491a1a4b31fa7315a13461ce83134aa4b26dd2fd5a073e"
+        "a64bdce0ff60dc679628723ca2801beed420f90fce4273e90084294a80fe954c54921d307f879105"
+        "33c51fbab6e7d9d56f34eba7b00ac0f53617ee750c17818ee4c9735230c2bbed132eb6a69c7927d2"
+        "f147046dd88d9eac8e3799d45af3473b1b253b9bfc37aa6703ce6bcee38493b84e8760b8c70eab29"
+        "439381c3303eb58accec46183934604ef8c9c3"
+    );
+
+    let sk = SecretKey::from(BOB_SECRET_KEY);
+    assert_eq!(ChaChaBox::unseal(&sk, SEAL_CIPHERTEXT).unwrap(), PLAINTEXT);
+
+    #[cfg(feature = "salsa20")]
+    assert!(sk.unseal(SEAL_CIPHERTEXT).is_err());
+}
+
+#[cfg(all(feature = "seal", feature = "alloc", feature = "salsa20"))]
+#[test]
+fn seal_padded() {
+    use crypto_box::padding::Padding;
+
+    let pk = PublicKey::from(BOB_PUBLIC_KEY);
+    let sk = SecretKey::from(BOB_SECRET_KEY);
+
+    for len in [0, 1, 31, 32, 33] {
+        let plaintext = vec![0x42; len];
+        let sealed = pk
+            .seal_padded(&mut OsRng, &plaintext, Padding::Block(32))
+            .unwrap();
+        assert_eq!(sealed.len(), crypto_box::SEALBYTES + (len / 32 + 1) * 32);
+        assert_eq!(
+            sk.unseal_padded(&sealed, Padding::Block(32)).unwrap(),
+            plaintext
+        );
+    }
+
+    // Sealed without padding
+    let sealed = pk.seal(&mut OsRng, &[0x42; 32]).unwrap();
+    assert!(sk.unseal_padded(&sealed, Padding::Block(32)).is_err());
+}
+
+#[cfg(feature = "salsa20")]
+#[test]
+fn container() {
+    use crypto_box::{container::Algorithm, SalsaBox};
+
+    let alice_secret_key = SecretKey::from(ALICE_SECRET_KEY);
+    let bob_secret_key = SecretKey::from(BOB_SECRET_KEY);
+    let alice_box = SalsaBox::new(&PublicKey::from(BOB_PUBLIC_KEY), &alice_secret_key);
+    let bob_box = SalsaBox::new(&PublicKey::from(ALICE_PUBLIC_KEY), &bob_secret_key);
+
+    let container = alice_box
+        .encrypt_to_container(&mut OsRng, PLAINTEXT)
+        .unwrap();
+    assert_eq!(container[1], Algorithm::XSalsa20Poly1305.id());
+    assert_eq!(bob_box.open_container(&container).unwrap(), PLAINTEXT);
+    assert_eq!(
+        bob_secret_key
+            .open_container(&PublicKey::from(ALICE_PUBLIC_KEY), &container)
+            .unwrap(),
+        PLAINTEXT
+    );
+
+    #[cfg(feature = "chacha20")]
+    {
+        use crypto_box::ChaChaBox;
+
+        let bob_chacha_box = ChaChaBox::new(&PublicKey::from(ALICE_PUBLIC_KEY), &bob_secret_key);
+        assert!(bob_chacha_box.open_container(&container).is_err());
+
+        let container = ChaChaBox::new(&PublicKey::from(BOB_PUBLIC_KEY), &alice_secret_key)
+            .encrypt_to_container(&mut OsRng, PLAINTEXT)
+            .unwrap();
+        assert_eq!(container[1], Algorithm::XChaCha20Poly1305.id());
+        assert!(bob_box.open_container(&container).is_err());
+        assert_eq!(
+            bob_secret_key
+                .open_container(&PublicKey::from(ALICE_PUBLIC_KEY), &container)
+                .unwrap(),
+            PLAINTEXT
+        );
+    }
 }
@@ -0,0 +1,56 @@
+//! Batch encryption and decryption of many messages under the same key.
+//!
+//! When the `rayon` feature is enabled, the messages in a batch are processed
+//! in parallel. Otherwise they're processed sequentially.
+
+use crate::{Error, Kdf, Nonce, SecretBox};
+use aead::consts::{U32, U8};
+use alloc::vec::Vec;
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+
+impl<C> SecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Encrypt a batch of messages in-place, each under its own nonce.
+    ///
+    /// Each buffer has the layout expected by
+    /// [`SecretBox::encrypt_in_place_prefixed`]: [`SecretBox::TAG_SIZE`]
+    /// bytes of space for the tag, followed by the plaintext.
+    ///
+    /// Returns the result for each message, in the same order as `items`.
+    pub fn encrypt_batch(&self, items: &mut [(Nonce, &mut [u8])]) -> Vec<Result<(), Error>> {
+        #[cfg(feature = "rayon")]
+        let items = items.par_iter_mut();
+
+        #[cfg(not(feature = "rayon"))]
+        let items = items.iter_mut();
+
+        items
+            .map(|(nonce, buffer)| self.encrypt_in_place_prefixed(nonce, buffer))
+            .collect()
+    }
+
+    /// Verify and decrypt a batch of messages in-place, each under its own
+    /// nonce.
+    ///
+    /// Each buffer has the layout produced by [`SecretBox::encrypt_batch`],
+    /// and contains the plaintext in `buffer[TAG_SIZE..]` after successful
+    /// decryption. See [`SecretBox::decrypt_in_place_prefixed`].
+    ///
+    /// Returns the result for each message, in the same order as `items`.
+    pub fn decrypt_batch(&self, items: &mut [(Nonce, &mut [u8])]) -> Vec<Result<(), Error>> {
+        #[cfg(feature = "rayon")]
+        let items = items.par_iter_mut();
+
+        #[cfg(not(feature = "rayon"))]
+        let items = items.iter_mut();
+
+        items
+            .map(|(nonce, buffer)| self.decrypt_in_place_prefixed(nonce, buffer).map(|_| ()))
+            .collect()
+    }
+}
@@ -0,0 +1,225 @@
+//! Key-committing variant of [`SecretBox`].
+//!
+//! Poly1305 is not a collision resistant MAC: given two keys, it's possible to
+//! craft a single ciphertext which successfully decrypts under both of them.
+//! Applications which try to decrypt a ciphertext under several candidate
+//! keys are vulnerable to partitioning oracle attacks as a result, and a
+//! ciphertext can be made to decrypt to different messages for different
+//! recipients ("invisible salamanders").
+//!
+//! [`CommittingSecretBox`] prevents this by prepending a commitment to the
+//! key and nonce, which is checked alongside the Poly1305 tag.
+//!
+//! # Ciphertext layout
+//!
+//! ```text
+//! commitment (32 bytes) || Poly1305 tag (16 bytes) || ciphertext
+//! ```
+//!
+//! The commitment is computed by applying the cipher's [`Kdf`] (HSalsa20 or
+//! HChaCha20) to the per-message subkey, using the 16-byte ASCII string
+//! `"secretbox commit"` as input:
+//!
+//! ```text
+//! subkey     = Kdf(key, nonce[..16])
+//! commitment = Kdf(subkey, "secretbox commit")
+//! ```
+//!
+//! The tag and ciphertext following the commitment are identical to the output
+//! of the corresponding [`SecretBox`], and the commitment and tag are
+//! exposed together as a single 48-byte [`aead::Tag`] by the [`AeadInPlace`]
+//! detached methods.
+
+use crate::{Error, Kdf, Key, Nonce, SecretBox, SecretKey};
+use aead::{
+    consts::{U0, U24, U32, U48, U8},
+    generic_array::GenericArray,
+    AeadCore, AeadInPlace, Buffer, KeyInit, KeySizeUser,
+};
+use cipher::{IvSizeUser, KeyIvInit, StreamCipher};
+use subtle::ConstantTimeEq;
+
+/// Key-committing `crypto_secretbox` instantiated with the XChaCha20 stream
+/// cipher.
+#[cfg(feature = "chacha20")]
+pub type CommittingXChaCha20Poly1305 = CommittingSecretBox<chacha20::ChaCha20Legacy>;
+
+/// Key-committing `crypto_secretbox` instantiated with the XSalsa20 stream
+/// cipher.
+#[cfg(feature = "salsa20")]
+pub type CommittingXSalsa20Poly1305 = CommittingSecretBox<salsa20::Salsa20>;
+
+/// Input to the [`Kdf`] used to derive the commitment from the subkey.
+const COMMITMENT_INPUT: &[u8; 16] = b"secretbox commit";
+
+/// [`SecretBox`] with a key commitment prepended to the Poly1305 tag, which
+/// ensures a ciphertext can only be decrypted under the key (and nonce) it
+/// was created with.
+///
+/// See the [module-level documentation](self) for the ciphertext layout.
+pub struct CommittingSecretBox<C> {
+    secretbox: SecretBox<C>,
+}
+
+impl<C> CommittingSecretBox<C> {
+    /// Size of a key in bytes
+    pub const KEY_SIZE: usize = 32;
+
+    /// Size of a nonce in bytes
+    pub const NONCE_SIZE: usize = 24;
+
+    /// Size of the key commitment in bytes
+    pub const COMMITMENT_SIZE: usize = 32;
+
+    /// Size of the key commitment and Poly1305 tag in bytes
+    pub const TAG_SIZE: usize = Self::COMMITMENT_SIZE + SecretBox::<C>::TAG_SIZE;
+}
+
+impl<C> CommittingSecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Compute the key commitment for the given subkey.
+    fn commitment(subkey: &Key) -> Key {
+        C::kdf(subkey, GenericArray::from_slice(COMMITMENT_INPUT))
+    }
+}
+
+// Handwritten instead of derived to avoid `C: Clone` bound
+impl<C> Clone for CommittingSecretBox<C> {
+    fn clone(&self) -> Self {
+        Self {
+            secretbox: self.secretbox.clone(),
+        }
+    }
+}
+
+impl<C> From<SecretKey> for CommittingSecretBox<C> {
+    fn from(key: SecretKey) -> CommittingSecretBox<C> {
+        Self {
+            secretbox: key.into(),
+        }
+    }
+}
+
+impl<C> From<&SecretKey> for CommittingSecretBox<C> {
+    fn from(key: &SecretKey) -> CommittingSecretBox<C> {
+        key.clone().into()
+    }
+}
+
+impl<C> KeySizeUser for CommittingSecretBox<C> {
+    type KeySize = U32;
+}
+
+impl<C> KeyInit for CommittingSecretBox<C> {
+    fn new(key: &Key) -> Self {
+        SecretKey::from(*key).into()
+    }
+}
+
+impl<C> AeadCore for CommittingSecretBox<C> {
+    type NonceSize = U24;
+    type TagSize = U48;
+    type CiphertextOverhead = U0;
+}
+
+impl<C> AeadInPlace for CommittingSecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    fn encrypt_in_place(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), Error> {
+        // AAD unsupported
+        if !associated_data.is_empty() {
+            return Err(Error);
+        }
+
+        let pt_len = buffer.len();
+
+        // Make room in the buffer for the commitment and tag. They need to be
+        // prepended, so the message has to be moved.
+        buffer.extend_from_slice(aead::Tag::<Self>::default().as_slice())?;
+        buffer.as_mut().copy_within(..pt_len, Self::TAG_SIZE);
+
+        let (tag, body) = buffer.as_mut().split_at_mut(Self::TAG_SIZE);
+        let computed_tag = self.encrypt_in_place_detached(nonce, associated_data, body)?;
+        tag.copy_from_slice(&computed_tag);
+        Ok(())
+    }
+
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<aead::Tag<Self>, Error> {
+        // AAD unsupported
+        if !associated_data.is_empty() {
+            return Err(Error);
+        }
+
+        let subkey = self.secretbox.subkey(nonce);
+        let (mut cipher, mac) = SecretBox::<C>::init_cipher_and_mac_with_subkey(&subkey, nonce);
+        cipher.apply_keystream(buffer);
+
+        let mut tag = aead::Tag::<Self>::default();
+        let (commitment, poly1305_tag) = tag.split_at_mut(Self::COMMITMENT_SIZE);
+        commitment.copy_from_slice(&Self::commitment(&subkey));
+        poly1305_tag.copy_from_slice(&mac.compute_unpadded(buffer));
+        Ok(tag)
+    }
+
+    fn decrypt_in_place(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), Error> {
+        if buffer.len() < Self::TAG_SIZE {
+            return Err(Error);
+        }
+
+        let (tag, body) = buffer.as_mut().split_at_mut(Self::TAG_SIZE);
+        let tag = aead::Tag::<Self>::clone_from_slice(tag);
+        self.decrypt_in_place_detached(nonce, associated_data, body, &tag)?;
+
+        let pt_len = buffer.len() - Self::TAG_SIZE;
+        buffer.as_mut().copy_within(Self::TAG_SIZE.., 0);
+        buffer.truncate(pt_len);
+        Ok(())
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &aead::Tag<Self>,
+    ) -> Result<(), Error> {
+        // AAD unsupported
+        if !associated_data.is_empty() {
+            return Err(Error);
+        }
+
+        let subkey = self.secretbox.subkey(nonce);
+        let (mut cipher, mac) = SecretBox::<C>::init_cipher_and_mac_with_subkey(&subkey, nonce);
+        let expected_commitment = Self::commitment(&subkey);
+        let expected_tag = mac.compute_unpadded(buffer);
+
+        // Check the commitment and tag together in constant time
+        let (commitment, poly1305_tag) = tag.split_at(Self::COMMITMENT_SIZE);
+        let valid = expected_commitment.ct_eq(commitment) & expected_tag.ct_eq(poly1305_tag);
+
+        if valid.into() {
+            cipher.apply_keystream(buffer);
+            Ok(())
+        } else {
+            Err(Error)
+        }
+    }
+}
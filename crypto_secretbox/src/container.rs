@@ -0,0 +1,209 @@
+//! Self-describing container format for [`SecretBox`] ciphertexts.
+//!
+//! A container records which algorithm and nonce a ciphertext was produced
+//! with, so neither has to be tracked out of band:
+//!
+//! ```text
+//! version (1) || algorithm ID (1) || nonce (24) || Poly1305 tag (16) || ciphertext
+//! ```
+//!
+//! where everything following the nonce is the output of the corresponding
+//! `crypto_secretbox_easy` under a per-algorithm subkey, described below.
+//! [`SecretBox::open_container`] dispatches on the
+//! algorithm ID, so containers created with any supported algorithm can be
+//! opened with the same key, which allows stored data to migrate between
+//! algorithms over time.
+//!
+//! Each algorithm encrypts under its own subkey, derived with its [`Kdf`]
+//! from the key, the version and the algorithm ID. This keeps one key safe
+//! to use with several algorithms at once while data is being migrated, and
+//! binds the version and algorithm ID to the ciphertext: modifying either
+//! causes decryption to fail.
+
+use crate::{Error, Kdf, Nonce, SecretBox, SecretKey};
+use aead::{
+    consts::{U32, U8},
+    generic_array::GenericArray,
+    Aead,
+};
+use alloc::vec::Vec;
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+
+#[cfg(feature = "rand_core")]
+use {crate::nonce, aead::rand_core::CryptoRngCore};
+
+#[cfg(feature = "chacha20")]
+use chacha20::ChaCha20Legacy as ChaCha20;
+
+#[cfg(feature = "salsa20")]
+use salsa20::Salsa20;
+
+/// Current version of the container format.
+pub const VERSION: u8 = 1;
+
+/// Size of the container header in bytes.
+pub const HEADER_SIZE: usize = 2 + 24;
+
+/// Prefix of the input to the [`Kdf`] used to derive per-algorithm subkeys,
+/// which is followed by the version and algorithm ID.
+const SUBKEY_LABEL: &[u8; 14] = b"crypto_sbcntnr";
+
+/// Algorithm a container's ciphertext was produced with.
+///
+/// More algorithms may be added in the future.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// [`XSalsa20Poly1305`](crate::XSalsa20Poly1305).
+    XSalsa20Poly1305,
+
+    /// [`XChaCha20Poly1305`](crate::XChaCha20Poly1305).
+    XChaCha20Poly1305,
+}
+
+impl Algorithm {
+    /// Get the ID of this algorithm in the container format.
+    pub fn id(self) -> u8 {
+        match self {
+            Algorithm::XSalsa20Poly1305 => 1,
+            Algorithm::XChaCha20Poly1305 => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Algorithm {
+    type Error = Error;
+
+    fn try_from(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Algorithm::XSalsa20Poly1305),
+            2 => Ok(Algorithm::XChaCha20Poly1305),
+            _ => Err(Error),
+        }
+    }
+}
+
+/// Stream ciphers which [`SecretBox`] can be instantiated with to produce
+/// containers.
+pub trait ContainerAlgorithm {
+    /// Algorithm recorded in containers.
+    const ALGORITHM: Algorithm;
+}
+
+#[cfg(feature = "chacha20")]
+impl ContainerAlgorithm for ChaCha20 {
+    const ALGORITHM: Algorithm = Algorithm::XChaCha20Poly1305;
+}
+
+#[cfg(feature = "salsa20")]
+impl ContainerAlgorithm for Salsa20 {
+    const ALGORITHM: Algorithm = Algorithm::XSalsa20Poly1305;
+}
+
+/// Parsed container, borrowing from the serialized bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Container<'a> {
+    algorithm: Algorithm,
+    nonce: &'a Nonce,
+    ciphertext: &'a [u8],
+}
+
+impl<'a> Container<'a> {
+    /// Parse a serialized container.
+    ///
+    /// Returns [`Error`] if the version or algorithm is unknown, or the
+    /// container is too short to hold a Poly1305 tag.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_SIZE + 16 || bytes[0] != VERSION {
+            return Err(Error);
+        }
+
+        let (header, ciphertext) = bytes.split_at(HEADER_SIZE);
+        Ok(Self {
+            algorithm: header[1].try_into()?,
+            nonce: Nonce::from_slice(&header[2..]),
+            ciphertext,
+        })
+    }
+
+    /// Get the algorithm the ciphertext was produced with.
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Get the nonce the ciphertext was produced with.
+    pub fn nonce(&self) -> &'a Nonce {
+        self.nonce
+    }
+
+    /// Get the ciphertext, laid out as a Poly1305 tag followed by the
+    /// encrypted message.
+    pub fn ciphertext(&self) -> &'a [u8] {
+        self.ciphertext
+    }
+}
+
+impl<C> SecretBox<C>
+where
+    C: ContainerAlgorithm
+        + Kdf
+        + KeyIvInit
+        + KeySizeUser<KeySize = U32>
+        + IvSizeUser<IvSize = U8>
+        + StreamCipher,
+{
+    /// Encrypt `plaintext` under a random nonce, returning a container which
+    /// records the algorithm and nonce.
+    #[cfg(feature = "rand_core")]
+    pub fn encrypt_to_container(
+        &self,
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let nonce = nonce::generate(csprng);
+        let ciphertext =
+            subkey_secretbox::<C>(&self.key, C::ALGORITHM).encrypt(&nonce, plaintext)?;
+
+        let mut container = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+        container.push(VERSION);
+        container.push(C::ALGORITHM.id());
+        container.extend_from_slice(&nonce);
+        container.extend_from_slice(&ciphertext);
+        Ok(container)
+    }
+}
+
+impl<C> SecretBox<C> {
+    /// Decrypt a container using this [`SecretBox`]'s key and the algorithm
+    /// recorded in the container, which may differ from `C`.
+    ///
+    /// Returns [`Error`] if the container can't be parsed, its algorithm
+    /// isn't enabled, or decryption fails.
+    pub fn open_container(&self, container: &[u8]) -> Result<Vec<u8>, Error> {
+        let container = Container::parse(container)?;
+
+        match container.algorithm() {
+            #[cfg(feature = "salsa20")]
+            Algorithm::XSalsa20Poly1305 => {
+                subkey_secretbox::<Salsa20>(&self.key, Algorithm::XSalsa20Poly1305)
+                    .decrypt(container.nonce(), container.ciphertext())
+            }
+            #[cfg(feature = "chacha20")]
+            Algorithm::XChaCha20Poly1305 => {
+                subkey_secretbox::<ChaCha20>(&self.key, Algorithm::XChaCha20Poly1305)
+                    .decrypt(container.nonce(), container.ciphertext())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error),
+        }
+    }
+}
+
+/// Derive the [`SecretBox`] used to produce containers with `algorithm`.
+fn subkey_secretbox<C: Kdf>(key: &SecretKey, algorithm: Algorithm) -> SecretBox<C> {
+    let mut input = [0u8; 16];
+    input[..14].copy_from_slice(SUBKEY_LABEL);
+    input[14] = VERSION;
+    input[15] = algorithm.id();
+    SecretKey::from(C::kdf(key.as_ref(), GenericArray::from_slice(&input))).into()
+}
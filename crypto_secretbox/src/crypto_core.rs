@@ -0,0 +1,231 @@
+//! HSalsa20 and HChaCha20 core functions, i.e. NaCl's `crypto_core`.
+//!
+//! These derive a 32-byte output from a 32-byte key and a 16-byte input,
+//! optionally using a custom 16-byte constant in place of the standard
+//! `"expand 32-byte k"`. With the standard constant, they're the [`Kdf`]
+//! used by [`SecretBox`] to derive per-message subkeys.
+//!
+//! [`Kdf`]: crate::Kdf
+//! [`SecretBox`]: crate::SecretBox
+
+use crate::{Kdf, Key};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "chacha20")]
+use chacha20::ChaCha20Legacy as ChaCha20;
+
+#[cfg(feature = "salsa20")]
+use salsa20::Salsa20;
+
+/// Size of the input in bytes.
+pub const INPUT_SIZE: usize = 16;
+
+/// Size of the constant in bytes.
+pub const CONSTANT_SIZE: usize = 16;
+
+/// Number of double rounds.
+const DOUBLE_ROUNDS: usize = 10;
+
+/// Compute HSalsa20 of the given input under the given key.
+///
+/// If `constant` is `None`, the standard `"expand 32-byte k"` constant is
+/// used. This is equivalent to libsodium's `crypto_core_hsalsa20`.
+#[cfg(feature = "salsa20")]
+pub fn hsalsa20(
+    key: &Key,
+    input: &[u8; INPUT_SIZE],
+    constant: Option<&[u8; CONSTANT_SIZE]>,
+) -> Key {
+    let constant = match constant {
+        Some(constant) => read_words::<4>(constant),
+        None => return Salsa20::kdf(key, input.into()),
+    };
+
+    let key = Zeroizing::new(read_words::<8>(key));
+    let input = read_words::<4>(input);
+
+    let mut state = Zeroizing::new([
+        constant[0],
+        key[0],
+        key[1],
+        key[2], //
+        key[3],
+        constant[1],
+        input[0],
+        input[1], //
+        input[2],
+        input[3],
+        constant[2],
+        key[4], //
+        key[5],
+        key[6],
+        key[7],
+        constant[3],
+    ]);
+
+    for _ in 0..DOUBLE_ROUNDS {
+        // Column round
+        salsa_quarter_round(&mut state, 0, 4, 8, 12);
+        salsa_quarter_round(&mut state, 5, 9, 13, 1);
+        salsa_quarter_round(&mut state, 10, 14, 2, 6);
+        salsa_quarter_round(&mut state, 15, 3, 7, 11);
+
+        // Row round
+        salsa_quarter_round(&mut state, 0, 1, 2, 3);
+        salsa_quarter_round(&mut state, 5, 6, 7, 4);
+        salsa_quarter_round(&mut state, 10, 11, 8, 9);
+        salsa_quarter_round(&mut state, 15, 12, 13, 14);
+    }
+
+    write_words(&[
+        state[0], state[5], state[10], state[15], state[6], state[7], state[8], state[9],
+    ])
+}
+
+/// Compute HChaCha20 of the given input under the given key.
+///
+/// If `constant` is `None`, the standard `"expand 32-byte k"` constant is
+/// used. This is equivalent to libsodium's `crypto_core_hchacha20`.
+#[cfg(feature = "chacha20")]
+pub fn hchacha20(
+    key: &Key,
+    input: &[u8; INPUT_SIZE],
+    constant: Option<&[u8; CONSTANT_SIZE]>,
+) -> Key {
+    let constant = match constant {
+        Some(constant) => read_words::<4>(constant),
+        None => return ChaCha20::kdf(key, input.into()),
+    };
+
+    let key = Zeroizing::new(read_words::<8>(key));
+    let input = read_words::<4>(input);
+
+    let mut state = Zeroizing::new([
+        constant[0],
+        constant[1],
+        constant[2],
+        constant[3], //
+        key[0],
+        key[1],
+        key[2],
+        key[3], //
+        key[4],
+        key[5],
+        key[6],
+        key[7], //
+        input[0],
+        input[1],
+        input[2],
+        input[3],
+    ]);
+
+    chacha_rounds(&mut state);
+
+    write_words(&[
+        state[0], state[1], state[2], state[3], state[12], state[13], state[14], state[15],
+    ])
+}
+
+/// Compute a block of the original ChaCha20 keystream, which has a 64-bit
+/// block counter followed by a 64-bit nonce.
+#[cfg(feature = "chacha20")]
+pub(crate) fn chacha20_block(key: &Key, counter: u64, nonce: &[u8; 8]) -> Zeroizing<[u8; 64]> {
+    let constant = read_words::<4>(b"expand 32-byte k");
+    let key = Zeroizing::new(read_words::<8>(key));
+    let nonce = read_words::<2>(nonce);
+
+    let input = Zeroizing::new([
+        constant[0],
+        constant[1],
+        constant[2],
+        constant[3], //
+        key[0],
+        key[1],
+        key[2],
+        key[3], //
+        key[4],
+        key[5],
+        key[6],
+        key[7], //
+        counter as u32,
+        (counter >> 32) as u32,
+        nonce[0],
+        nonce[1],
+    ]);
+
+    let mut state = input.clone();
+    chacha_rounds(&mut state);
+
+    let mut output = Zeroizing::new([0u8; 64]);
+    for ((chunk, word), input) in output
+        .chunks_exact_mut(4)
+        .zip(state.iter())
+        .zip(input.iter())
+    {
+        chunk.copy_from_slice(&word.wrapping_add(*input).to_le_bytes());
+    }
+
+    output
+}
+
+/// The ChaCha20 double rounds.
+#[cfg(feature = "chacha20")]
+fn chacha_rounds(state: &mut [u32; 16]) {
+    for _ in 0..DOUBLE_ROUNDS {
+        // Column round
+        chacha_quarter_round(state, 0, 4, 8, 12);
+        chacha_quarter_round(state, 1, 5, 9, 13);
+        chacha_quarter_round(state, 2, 6, 10, 14);
+        chacha_quarter_round(state, 3, 7, 11, 15);
+
+        // Diagonal round
+        chacha_quarter_round(state, 0, 5, 10, 15);
+        chacha_quarter_round(state, 1, 6, 11, 12);
+        chacha_quarter_round(state, 2, 7, 8, 13);
+        chacha_quarter_round(state, 3, 4, 9, 14);
+    }
+}
+
+/// The Salsa20 quarter round function.
+#[cfg(feature = "salsa20")]
+fn salsa_quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[b] ^= state[a].wrapping_add(state[d]).rotate_left(7);
+    state[c] ^= state[b].wrapping_add(state[a]).rotate_left(9);
+    state[d] ^= state[c].wrapping_add(state[b]).rotate_left(13);
+    state[a] ^= state[d].wrapping_add(state[c]).rotate_left(18);
+}
+
+/// The ChaCha20 quarter round function.
+#[cfg(feature = "chacha20")]
+fn chacha_quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// Read little endian words from bytes.
+fn read_words<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut words = [0u32; N];
+
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    words
+}
+
+/// Write words as little endian bytes.
+fn write_words(words: &[u32; 8]) -> Key {
+    let mut output = Key::default();
+
+    for (chunk, word) in output.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    output
+}
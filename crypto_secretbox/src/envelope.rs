@@ -0,0 +1,279 @@
+//! Envelope encryption, i.e. encrypting data under a fresh data encryption
+//! key (DEK) which is in turn wrapped under a long-lived key encryption key
+//! (KEK).
+//!
+//! Each [`Envelope`] holds the payload encrypted with a [`SecretBox`] under
+//! its own randomly generated DEK, along with the DEK encrypted under the
+//! KEK. The KEK is identified by an opaque key ID stored in the envelope, so
+//! the recipient can look up which KEK to open it with.
+//!
+//! Rotating the KEK only requires [re-wrapping] the DEK: the payload
+//! ciphertext is left untouched.
+//!
+//! The DEK isn't wrapped under the KEK directly, but under a subkey derived
+//! from it, the version and the key ID. Relabeling an envelope with another
+//! key ID therefore causes it to fail to open, so the key ID can be trusted
+//! once it has.
+//!
+//! # Format
+//!
+//! Envelopes are serialized as:
+//!
+//! ```text
+//! version (1) || key ID length (1) || key ID || wrap nonce (24)
+//!     || wrapped DEK (48) || nonce (24) || payload ciphertext
+//! ```
+//!
+//! where the wrapped DEK and payload ciphertext are both laid out as a
+//! Poly1305 tag followed by the ciphertext, like `crypto_secretbox_easy`.
+//!
+//! [re-wrapping]: Envelope::rewrap
+
+use crate::{Error, Kdf, Nonce, SecretBox, SecretKey};
+use aead::{
+    consts::{U32, U8},
+    generic_array::GenericArray,
+    Aead,
+};
+use alloc::vec::Vec;
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "rand_core")]
+use {crate::nonce, aead::rand_core::CryptoRngCore};
+
+/// Current version of the envelope format.
+pub const VERSION: u8 = 1;
+
+/// Maximum length of a key ID in bytes.
+pub const MAX_KEY_ID_SIZE: usize = 255;
+
+/// Size of a wrapped data encryption key in bytes.
+pub const WRAPPED_KEY_SIZE: usize = SecretKey::BYTES + TAG_SIZE;
+
+/// Input to the [`Kdf`] used to derive the key a data encryption key is
+/// wrapped under, before the version and key ID are absorbed.
+const WRAP_KEY_LABEL: &[u8; 16] = b"crypto_sbenv_wrp";
+
+/// Size of a nonce in bytes.
+const NONCE_SIZE: usize = 24;
+
+/// Size of a Poly1305 tag in bytes.
+const TAG_SIZE: usize = 16;
+
+/// Payload encrypted under a data encryption key, which is wrapped under a
+/// key encryption key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Envelope {
+    /// ID of the key encryption key.
+    key_id: Vec<u8>,
+
+    /// Nonce the data encryption key is wrapped under.
+    wrap_nonce: Nonce,
+
+    /// Wrapped data encryption key.
+    wrapped_key: [u8; WRAPPED_KEY_SIZE],
+
+    /// Nonce the payload is encrypted under.
+    nonce: Nonce,
+
+    /// Encrypted payload.
+    ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Encrypt `plaintext` under a freshly generated data encryption key,
+    /// which is wrapped under `kek` and labeled with `key_id`.
+    ///
+    /// Returns [`Error`] if `key_id` is longer than [`MAX_KEY_ID_SIZE`].
+    #[cfg(feature = "rand_core")]
+    pub fn seal<C>(
+        kek: &SecretBox<C>,
+        key_id: &[u8],
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+    ) -> Result<Self, Error>
+    where
+        C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    {
+        if key_id.len() > MAX_KEY_ID_SIZE {
+            return Err(Error);
+        }
+
+        let dek = SecretKey::generate(csprng);
+        let (wrap_nonce, wrapped_key) = wrap_key(kek, key_id, &dek, csprng)?;
+
+        let nonce = nonce::generate(csprng);
+        let ciphertext = SecretBox::<C>::from(dek).encrypt(&nonce, plaintext)?;
+
+        Ok(Self {
+            key_id: key_id.to_vec(),
+            wrap_nonce,
+            wrapped_key,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Unwrap the data encryption key using `kek` and decrypt the payload.
+    ///
+    /// Returns [`Error`] if decryption fails, including if the envelope has
+    /// been relabeled with a different key ID.
+    pub fn open<C>(&self, kek: &SecretBox<C>) -> Result<Vec<u8>, Error>
+    where
+        C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    {
+        let dek = self.unwrap_key(kek)?;
+        SecretBox::<C>::from(dek).decrypt(&self.nonce, self.ciphertext.as_slice())
+    }
+
+    /// Re-wrap the data encryption key under `new_kek`, labeled with
+    /// `new_key_id`, after unwrapping it using `kek`.
+    ///
+    /// The payload ciphertext is left untouched, as is the envelope if an
+    /// error is returned.
+    #[cfg(feature = "rand_core")]
+    pub fn rewrap<C>(
+        &mut self,
+        kek: &SecretBox<C>,
+        new_kek: &SecretBox<C>,
+        new_key_id: &[u8],
+        csprng: &mut impl CryptoRngCore,
+    ) -> Result<(), Error>
+    where
+        C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    {
+        if new_key_id.len() > MAX_KEY_ID_SIZE {
+            return Err(Error);
+        }
+
+        let dek = self.unwrap_key(kek)?;
+        let (wrap_nonce, wrapped_key) = wrap_key(new_kek, new_key_id, &dek, csprng)?;
+
+        self.key_id = new_key_id.to_vec();
+        self.wrap_nonce = wrap_nonce;
+        self.wrapped_key = wrapped_key;
+        Ok(())
+    }
+
+    /// Get the ID of the key encryption key the data encryption key is
+    /// wrapped under.
+    pub fn key_id(&self) -> &[u8] {
+        &self.key_id
+    }
+
+    /// Parse a serialized envelope.
+    ///
+    /// Returns [`Error`] if the version is unsupported or `bytes` is
+    /// truncated.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let (&version, rest) = bytes.split_first().ok_or(Error)?;
+        if version != VERSION {
+            return Err(Error);
+        }
+
+        let (&key_id_len, rest) = rest.split_first().ok_or(Error)?;
+        let (key_id, rest) = split_at(rest, key_id_len.into())?;
+        let (wrap_nonce, rest) = split_at(rest, NONCE_SIZE)?;
+        let (wrapped_key, rest) = split_at(rest, WRAPPED_KEY_SIZE)?;
+        let (nonce, ciphertext) = split_at(rest, NONCE_SIZE)?;
+
+        if ciphertext.len() < TAG_SIZE {
+            return Err(Error);
+        }
+
+        Ok(Self {
+            key_id: key_id.to_vec(),
+            wrap_nonce: *Nonce::from_slice(wrap_nonce),
+            wrapped_key: wrapped_key.try_into().map_err(|_| Error)?,
+            nonce: *Nonce::from_slice(nonce),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    /// Serialize this envelope.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            2 + self.key_id.len()
+                + self.wrap_nonce.len()
+                + WRAPPED_KEY_SIZE
+                + self.nonce.len()
+                + self.ciphertext.len(),
+        );
+        bytes.push(VERSION);
+        bytes.push(self.key_id.len() as u8);
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.wrap_nonce);
+        bytes.extend_from_slice(&self.wrapped_key);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// Unwrap the data encryption key using `kek`.
+    fn unwrap_key<C>(&self, kek: &SecretBox<C>) -> Result<SecretKey, Error>
+    where
+        C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    {
+        let mut buffer = Zeroizing::new(self.wrapped_key);
+        let key = wrapping_secretbox(kek, &self.key_id)
+            .decrypt_in_place_prefixed(&self.wrap_nonce, &mut *buffer)?;
+        SecretKey::from_slice(key).map_err(|_| Error)
+    }
+}
+
+/// Wrap the data encryption key under `kek`, bound to `key_id`, using a
+/// random nonce.
+#[cfg(feature = "rand_core")]
+fn wrap_key<C>(
+    kek: &SecretBox<C>,
+    key_id: &[u8],
+    dek: &SecretKey,
+    csprng: &mut impl CryptoRngCore,
+) -> Result<(Nonce, [u8; WRAPPED_KEY_SIZE]), Error>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    let nonce = nonce::generate(csprng);
+    let mut wrapped_key = [0u8; WRAPPED_KEY_SIZE];
+    wrapped_key[TAG_SIZE..].copy_from_slice(dek.as_bytes());
+    wrapping_secretbox(kek, key_id).encrypt_in_place_prefixed(&nonce, &mut wrapped_key)?;
+    Ok((nonce, wrapped_key))
+}
+
+/// Derive the [`SecretBox`] which wraps data encryption keys labeled with
+/// `key_id` from `kek`.
+///
+/// The version, key ID length and key ID are absorbed 16 bytes at a time by
+/// chaining the [`Kdf`]. The length prefix makes zero padding the last block
+/// unambiguous.
+fn wrapping_secretbox<C: Kdf>(kek: &SecretBox<C>, key_id: &[u8]) -> SecretBox<C> {
+    debug_assert!(key_id.len() <= MAX_KEY_ID_SIZE);
+
+    let mut input = Vec::with_capacity(2 + key_id.len());
+    input.push(VERSION);
+    input.push(key_id.len() as u8);
+    input.extend_from_slice(key_id);
+
+    let mut key = Zeroizing::new(C::kdf(
+        kek.secret_key().as_ref(),
+        GenericArray::from_slice(WRAP_KEY_LABEL),
+    ));
+
+    for chunk in input.chunks(16) {
+        let mut block = [0u8; 16];
+        block[..chunk.len()].copy_from_slice(chunk);
+        *key = C::kdf(&key, GenericArray::from_slice(&block));
+    }
+
+    SecretKey::from(*key).into()
+}
+
+/// Split `bytes` at `mid`, returning [`Error`] if it's too short.
+fn split_at(bytes: &[u8], mid: usize) -> Result<(&[u8], &[u8]), Error> {
+    if bytes.len() < mid {
+        return Err(Error);
+    }
+
+    Ok(bytes.split_at(mid))
+}
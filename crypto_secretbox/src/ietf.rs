@@ -0,0 +1,267 @@
+//! IETF ChaCha20Poly1305 and XChaCha20Poly1305 AEAD ciphers, i.e. libsodium's
+//! `crypto_aead_chacha20poly1305_ietf` and
+//! `crypto_aead_xchacha20poly1305_ietf`.
+//!
+//! Unlike [`SecretBox`], these support associated data, which is
+//! authenticated along with the message but not encrypted.
+//!
+//! [`ChaCha20Poly1305Ietf`] is the AEAD construction from [RFC 8439], which
+//! takes a 96-bit nonce. [`XChaCha20Poly1305Ietf`] extends it to a 192-bit
+//! nonce, which is large enough to be chosen at random, by deriving a subkey
+//! from the key and the first 16 bytes of the nonce using HChaCha20 (the same
+//! [`Kdf`] used by [`XChaCha20Poly1305`]), as described in
+//! [`draft-irtf-cfrg-xchacha`].
+//!
+//! # Ciphertext layout
+//!
+//! The [`AeadInPlace`] detached methods produce libsodium's detached layout,
+//! i.e. the ciphertext and a separate Poly1305 tag. The combined layout, as
+//! produced by `crypto_aead_*_encrypt` and the [`aead::Aead`] methods, appends
+//! the tag to the ciphertext:
+//!
+//! ```text
+//! ciphertext || Poly1305 tag (16 bytes)
+//! ```
+//!
+//! [`SecretBox`]: crate::SecretBox
+//! [`XChaCha20Poly1305`]: crate::XChaCha20Poly1305
+//! [RFC 8439]: https://datatracker.ietf.org/doc/html/rfc8439
+//! [`draft-irtf-cfrg-xchacha`]: https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha
+
+use crate::{Error, Kdf, Key, SecretKey, Tag};
+use aead::{
+    consts::{U0, U12, U16, U24, U32},
+    generic_array::GenericArray,
+    AeadCore, AeadInPlace, KeyInit, KeySizeUser,
+};
+use chacha20::{ChaCha20, ChaCha20Legacy};
+use cipher::{KeyIvInit, StreamCipher};
+use poly1305::{universal_hash::UniversalHash, Poly1305};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// ChaCha20Poly1305 AEAD cipher with a 96-bit nonce, as described in
+/// [RFC 8439].
+///
+/// This is equivalent to libsodium's `crypto_aead_chacha20poly1305_ietf`.
+///
+/// Nonces are too short to be safely chosen at random: use a counter, or
+/// [`XChaCha20Poly1305Ietf`] instead.
+///
+/// [RFC 8439]: https://datatracker.ietf.org/doc/html/rfc8439
+#[derive(Clone)]
+pub struct ChaCha20Poly1305Ietf {
+    /// Secret key.
+    key: SecretKey,
+}
+
+impl ChaCha20Poly1305Ietf {
+    /// Size of a key in bytes
+    pub const KEY_SIZE: usize = 32;
+
+    /// Size of a nonce in bytes
+    pub const NONCE_SIZE: usize = 12;
+
+    /// Size of a Poly1305 tag in bytes
+    pub const TAG_SIZE: usize = 16;
+}
+
+impl From<SecretKey> for ChaCha20Poly1305Ietf {
+    fn from(key: SecretKey) -> ChaCha20Poly1305Ietf {
+        Self { key }
+    }
+}
+
+impl From<&SecretKey> for ChaCha20Poly1305Ietf {
+    fn from(key: &SecretKey) -> ChaCha20Poly1305Ietf {
+        key.clone().into()
+    }
+}
+
+impl KeySizeUser for ChaCha20Poly1305Ietf {
+    type KeySize = U32;
+}
+
+impl KeyInit for ChaCha20Poly1305Ietf {
+    fn new(key: &Key) -> Self {
+        SecretKey::from(*key).into()
+    }
+}
+
+impl AeadCore for ChaCha20Poly1305Ietf {
+    type NonceSize = U12;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl AeadInPlace for ChaCha20Poly1305Ietf {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &GenericArray<u8, U12>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Tag, Error> {
+        encrypt_in_place_detached(self.key.as_ref(), nonce, associated_data, buffer)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &GenericArray<u8, U12>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        decrypt_in_place_detached(self.key.as_ref(), nonce, associated_data, buffer, tag)
+    }
+}
+
+/// XChaCha20Poly1305 AEAD cipher with a 192-bit nonce, as described in
+/// [`draft-irtf-cfrg-xchacha`].
+///
+/// This is equivalent to libsodium's `crypto_aead_xchacha20poly1305_ietf`.
+///
+/// Not to be confused with [`XChaCha20Poly1305`], which is `crypto_secretbox`
+/// instantiated with XChaCha20 and doesn't support associated data.
+///
+/// [`draft-irtf-cfrg-xchacha`]: https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha
+/// [`XChaCha20Poly1305`]: crate::XChaCha20Poly1305
+#[derive(Clone)]
+pub struct XChaCha20Poly1305Ietf {
+    /// Secret key.
+    key: SecretKey,
+}
+
+impl XChaCha20Poly1305Ietf {
+    /// Size of a key in bytes
+    pub const KEY_SIZE: usize = 32;
+
+    /// Size of a nonce in bytes
+    pub const NONCE_SIZE: usize = 24;
+
+    /// Size of a Poly1305 tag in bytes
+    pub const TAG_SIZE: usize = 16;
+
+    /// Derive the subkey and IETF nonce for the given extended nonce.
+    fn subkey_and_nonce(
+        &self,
+        nonce: &GenericArray<u8, U24>,
+    ) -> (Zeroizing<Key>, GenericArray<u8, U12>) {
+        let subkey = Zeroizing::new(ChaCha20Legacy::kdf(
+            self.key.as_ref(),
+            GenericArray::from_slice(&nonce[..16]),
+        ));
+
+        let mut ietf_nonce = GenericArray::<u8, U12>::default();
+        ietf_nonce[4..].copy_from_slice(&nonce[16..]);
+        (subkey, ietf_nonce)
+    }
+}
+
+impl From<SecretKey> for XChaCha20Poly1305Ietf {
+    fn from(key: SecretKey) -> XChaCha20Poly1305Ietf {
+        Self { key }
+    }
+}
+
+impl From<&SecretKey> for XChaCha20Poly1305Ietf {
+    fn from(key: &SecretKey) -> XChaCha20Poly1305Ietf {
+        key.clone().into()
+    }
+}
+
+impl KeySizeUser for XChaCha20Poly1305Ietf {
+    type KeySize = U32;
+}
+
+impl KeyInit for XChaCha20Poly1305Ietf {
+    fn new(key: &Key) -> Self {
+        SecretKey::from(*key).into()
+    }
+}
+
+impl AeadCore for XChaCha20Poly1305Ietf {
+    type NonceSize = U24;
+    type TagSize = U16;
+    type CiphertextOverhead = U0;
+}
+
+impl AeadInPlace for XChaCha20Poly1305Ietf {
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &GenericArray<u8, U24>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Tag, Error> {
+        let (subkey, nonce) = self.subkey_and_nonce(nonce);
+        encrypt_in_place_detached(&subkey, &nonce, associated_data, buffer)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &GenericArray<u8, U24>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        let (subkey, nonce) = self.subkey_and_nonce(nonce);
+        decrypt_in_place_detached(&subkey, &nonce, associated_data, buffer, tag)
+    }
+}
+
+/// Encrypt `buffer` in-place with the RFC 8439 AEAD construction, returning
+/// the Poly1305 tag.
+fn encrypt_in_place_detached(
+    key: &Key,
+    nonce: &GenericArray<u8, U12>,
+    associated_data: &[u8],
+    buffer: &mut [u8],
+) -> Result<Tag, Error> {
+    let (mut cipher, mac) = init_cipher_and_mac(key, nonce);
+    cipher.try_apply_keystream(buffer).map_err(|_| Error)?;
+    Ok(compute_tag(mac, associated_data, buffer))
+}
+
+/// Verify the Poly1305 tag and decrypt `buffer` in-place with the RFC 8439
+/// AEAD construction.
+fn decrypt_in_place_detached(
+    key: &Key,
+    nonce: &GenericArray<u8, U12>,
+    associated_data: &[u8],
+    buffer: &mut [u8],
+    tag: &Tag,
+) -> Result<(), Error> {
+    let (mut cipher, mac) = init_cipher_and_mac(key, nonce);
+    let expected_tag = compute_tag(mac, associated_data, buffer);
+
+    if expected_tag.ct_eq(tag).into() {
+        cipher.try_apply_keystream(buffer).map_err(|_| Error)
+    } else {
+        Err(Error)
+    }
+}
+
+/// Initialize the ChaCha20 cipher at block 1 and the Poly1305 MAC keyed with
+/// the first 32 bytes of block 0.
+fn init_cipher_and_mac(key: &Key, nonce: &GenericArray<u8, U12>) -> (ChaCha20, Poly1305) {
+    let mut cipher = ChaCha20::new(key, nonce);
+
+    let mut block = Zeroizing::new([0u8; 64]);
+    cipher.apply_keystream(&mut *block);
+
+    let mac = Poly1305::new(GenericArray::from_slice(&block[..32]));
+    (cipher, mac)
+}
+
+/// Compute the Poly1305 tag over the associated data and ciphertext, each
+/// padded to 16 bytes, followed by their lengths.
+fn compute_tag(mut mac: Poly1305, associated_data: &[u8], ciphertext: &[u8]) -> Tag {
+    mac.update_padded(associated_data);
+    mac.update_padded(ciphertext);
+
+    let mut lengths = poly1305::Block::default();
+    lengths[..8].copy_from_slice(&(associated_data.len() as u64).to_le_bytes());
+    lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    mac.update(&[lengths]);
+
+    mac.finalize()
+}
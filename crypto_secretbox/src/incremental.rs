@@ -0,0 +1,174 @@
+//! Incremental (multi-part) encryption and decryption.
+//!
+//! These types make it possible to encrypt or decrypt messages which don't
+//! fit in memory, by processing them in chunks of arbitrary size. The result
+//! is identical to encrypting or decrypting the whole message at once using
+//! [`AeadInPlace::encrypt_in_place_detached`] and
+//! [`AeadInPlace::decrypt_in_place_detached`].
+//!
+//! Decryption happens in two passes over the ciphertext: the [`Verifier`]
+//! first authenticates the complete ciphertext, and only if it's authentic
+//! returns a [`Decryptor`] which is then used to decrypt it. This ensures no
+//! unauthenticated plaintext is ever released, but requires the ciphertext
+//! to be re-readable (e.g. stored in a file).
+//!
+//! [`SecretBox::encrypt_vectored_detached`] and
+//! [`SecretBox::decrypt_vectored_detached`] build on these types to encrypt
+//! and decrypt messages made up of several non-contiguous segments.
+//!
+//! [`AeadInPlace::encrypt_in_place_detached`]: crate::AeadInPlace::encrypt_in_place_detached
+//! [`AeadInPlace::decrypt_in_place_detached`]: crate::AeadInPlace::decrypt_in_place_detached
+
+use crate::{onetimeauth, Error, Kdf, Nonce, SecretBox, Tag};
+use aead::consts::{U32, U8};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+use core::ops::DerefMut;
+
+impl<C> SecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Start encrypting a message incrementally under the given nonce.
+    pub fn encryptor(&self, nonce: &Nonce) -> Encryptor<C> {
+        let (cipher, mac) = self.init_cipher_and_mac(nonce);
+
+        Encryptor {
+            cipher,
+            mac: onetimeauth::State::from_mac(mac),
+        }
+    }
+
+    /// Start authenticating a ciphertext incrementally under the given nonce,
+    /// in preparation for decrypting it.
+    pub fn verifier(&self, nonce: &Nonce) -> Verifier<C> {
+        let (cipher, mac) = self.init_cipher_and_mac(nonce);
+
+        Verifier {
+            cipher,
+            mac: onetimeauth::State::from_mac(mac),
+        }
+    }
+
+    /// Encrypt a message made up of several segments in-place, returning the
+    /// Poly1305 tag.
+    ///
+    /// The segments are treated as one contiguous message, so the result is
+    /// identical to concatenating them and using
+    /// [`AeadInPlace::encrypt_in_place_detached`]. Segments can be given as
+    /// `&mut [u8]` or [`std::io::IoSliceMut`].
+    ///
+    /// [`AeadInPlace::encrypt_in_place_detached`]: crate::AeadInPlace::encrypt_in_place_detached
+    /// [`std::io::IoSliceMut`]: https://doc.rust-lang.org/std/io/struct.IoSliceMut.html
+    pub fn encrypt_vectored_detached<S>(&self, nonce: &Nonce, segments: &mut [S]) -> Tag
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        let mut encryptor = self.encryptor(nonce);
+
+        for segment in segments.iter_mut() {
+            encryptor.update(segment);
+        }
+
+        encryptor.finalize()
+    }
+
+    /// Decrypt a message made up of several segments in-place, after checking
+    /// the Poly1305 tag over all of them.
+    ///
+    /// This is the inverse of [`SecretBox::encrypt_vectored_detached`]. If the
+    /// tag doesn't match, the segments are left untouched.
+    pub fn decrypt_vectored_detached<S>(
+        &self,
+        nonce: &Nonce,
+        segments: &mut [S],
+        tag: &Tag,
+    ) -> Result<(), Error>
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        let mut verifier = self.verifier(nonce);
+
+        for segment in segments.iter() {
+            verifier.update(segment);
+        }
+
+        let mut decryptor = verifier.verify(tag)?;
+
+        for segment in segments.iter_mut() {
+            decryptor.update(segment);
+        }
+
+        Ok(())
+    }
+}
+
+/// Incremental encryptor for a message.
+///
+/// Created using [`SecretBox::encryptor`].
+pub struct Encryptor<C> {
+    cipher: C,
+    mac: onetimeauth::State,
+}
+
+impl<C> Encryptor<C>
+where
+    C: StreamCipher,
+{
+    /// Encrypt the next chunk of the message in-place.
+    pub fn update(&mut self, buffer: &mut [u8]) {
+        self.cipher.apply_keystream(buffer);
+        self.mac.update(buffer);
+    }
+
+    /// Finish encrypting the message, returning the Poly1305 tag.
+    pub fn finalize(self) -> Tag {
+        self.mac.finalize()
+    }
+}
+
+/// Incremental authenticator for a ciphertext.
+///
+/// Created using [`SecretBox::verifier`].
+pub struct Verifier<C> {
+    cipher: C,
+    mac: onetimeauth::State,
+}
+
+impl<C> Verifier<C>
+where
+    C: StreamCipher,
+{
+    /// Authenticate the next chunk of the ciphertext.
+    pub fn update(&mut self, ciphertext: &[u8]) {
+        self.mac.update(ciphertext);
+    }
+
+    /// Check the complete ciphertext against the given tag in constant time.
+    ///
+    /// If the ciphertext is authentic, returns a [`Decryptor`] which must be
+    /// fed the same ciphertext again from the beginning.
+    pub fn verify(self, tag: &Tag) -> Result<Decryptor<C>, Error> {
+        self.mac.verify(tag)?;
+
+        Ok(Decryptor {
+            cipher: self.cipher,
+        })
+    }
+}
+
+/// Incremental decryptor for an authenticated ciphertext.
+///
+/// Obtained from [`Verifier::verify`].
+pub struct Decryptor<C> {
+    cipher: C,
+}
+
+impl<C> Decryptor<C>
+where
+    C: StreamCipher,
+{
+    /// Decrypt the next chunk of the ciphertext in-place.
+    pub fn update(&mut self, buffer: &mut [u8]) {
+        self.cipher.apply_keystream(buffer);
+    }
+}
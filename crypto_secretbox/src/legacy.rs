@@ -0,0 +1,72 @@
+//! Legacy NaCl `crypto_secretbox` API which operates on zero-padded buffers.
+//!
+//! The original NaCl C API (and many of its ports) expects the message to be
+//! prefixed with [`ZEROBYTES`] zero bytes, and produces a ciphertext which is
+//! prefixed with [`BOXZEROBYTES`] zero bytes followed by the Poly1305 tag:
+//!
+//! ```text
+//! message:    [0u8; ZEROBYTES]    || plaintext
+//! ciphertext: [0u8; BOXZEROBYTES] || tag || encrypted plaintext
+//! ```
+//!
+//! The functions in this module operate in-place on such buffers, which makes
+//! it possible to exchange them with C code using this convention as-is.
+
+use crate::{Error, Kdf, Nonce, SecretBox};
+use aead::consts::{U32, U8};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+
+/// Number of leading zero bytes in a `crypto_secretbox` message.
+pub const ZEROBYTES: usize = 32;
+
+/// Number of leading zero bytes in a `crypto_secretbox` ciphertext.
+pub const BOXZEROBYTES: usize = 16;
+
+/// Encrypt a zero-padded message in-place, equivalent to NaCl's
+/// `crypto_secretbox` function.
+///
+/// The first [`ZEROBYTES`] bytes of `buffer` must be zero. On success the
+/// buffer contains the zero-padded ciphertext.
+pub fn encrypt_in_place<C>(
+    secretbox: &SecretBox<C>,
+    nonce: &Nonce,
+    buffer: &mut [u8],
+) -> Result<(), Error>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    if buffer.len() < ZEROBYTES || !is_zero(&buffer[..ZEROBYTES]) {
+        return Err(Error);
+    }
+
+    secretbox.encrypt_in_place_prefixed(nonce, &mut buffer[BOXZEROBYTES..])
+}
+
+/// Decrypt a zero-padded ciphertext in-place, equivalent to NaCl's
+/// `crypto_secretbox_open` function.
+///
+/// The first [`BOXZEROBYTES`] bytes of `buffer` must be zero. On success the
+/// buffer contains the zero-padded message.
+pub fn decrypt_in_place<C>(
+    secretbox: &SecretBox<C>,
+    nonce: &Nonce,
+    buffer: &mut [u8],
+) -> Result<(), Error>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    if buffer.len() < ZEROBYTES || !is_zero(&buffer[..BOXZEROBYTES]) {
+        return Err(Error);
+    }
+
+    secretbox.decrypt_in_place_prefixed(nonce, &mut buffer[BOXZEROBYTES..])?;
+
+    // Replace the tag with the zero padding expected in front of the message
+    buffer[BOXZEROBYTES..ZEROBYTES].fill(0);
+    Ok(())
+}
+
+/// Is the given padding all zeroes?
+fn is_zero(padding: &[u8]) -> bool {
+    padding.iter().all(|&byte| byte == 0)
+}
@@ -84,6 +84,67 @@
 //! [5]: https://docs.rs/chacha20poly1305/latest/chacha20poly1305/struct.XChaCha20Poly1305.html
 //! [6]: https://tools.ietf.org/html/rfc8439
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod committing;
+pub mod incremental;
+pub mod legacy;
+pub mod nonce;
+pub mod onetimeauth;
+pub mod padding;
+pub mod siv;
+pub mod stream_cipher;
+
+#[cfg(any(feature = "chacha20", feature = "salsa20"))]
+pub mod crypto_core;
+
+#[cfg(feature = "chacha20")]
+pub mod ietf;
+
+#[cfg(feature = "stream")]
+pub mod stream;
+
+#[cfg(all(feature = "stream", feature = "std"))]
+pub mod seekable;
+
+#[cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+pub mod container;
+
+#[cfg(feature = "alloc")]
+pub mod envelope;
+
+#[cfg(feature = "argon2")]
+pub mod password;
+
+#[cfg(feature = "alloc")]
+mod batch;
+mod secret_key;
+
+pub use crate::secret_key::SecretKey;
+
+pub use crate::committing::CommittingSecretBox;
+
+#[cfg(feature = "chacha20")]
+pub use crate::committing::CommittingXChaCha20Poly1305;
+
+#[cfg(feature = "salsa20")]
+pub use crate::committing::CommittingXSalsa20Poly1305;
+
+pub use crate::siv::SivSecretBox;
+
+#[cfg(feature = "chacha20")]
+pub use crate::siv::XChaCha20Poly1305Siv;
+
+#[cfg(feature = "salsa20")]
+pub use crate::siv::XSalsa20Poly1305Siv;
+
+#[cfg(feature = "chacha20")]
+pub use crate::ietf::{ChaCha20Poly1305Ietf, XChaCha20Poly1305Ietf};
+
 pub use aead::{self, consts, AeadCore, AeadInPlace, Error, KeyInit, KeySizeUser};
 pub use cipher;
 
@@ -95,7 +156,8 @@ use aead::{
 use cipher::{IvSizeUser, KeyIvInit, StreamCipher};
 use core::marker::PhantomData;
 use poly1305::Poly1305;
-use zeroize::{Zeroize, Zeroizing};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
 
 #[cfg(feature = "chacha20")]
 use chacha20::{hchacha, ChaCha20Legacy as ChaCha20};
@@ -107,6 +169,8 @@ use salsa20::{hsalsa, Salsa20};
 use cipher::consts::U10;
 
 /// Key type.
+///
+/// See [`SecretKey`] for an owned key which is zeroized on drop.
 pub type Key = GenericArray<u8, U32>;
 
 /// Nonce type.
@@ -125,12 +189,11 @@ pub type Tag = GenericArray<u8, U16>;
 /// `crypto_secretbox` construction instantiated with XChaCha20.
 ///
 /// For new applications, we recommend using the `AEAD_XChaCha20_Poly1305`
-/// construction as described in [`draft-irtf-cfrg-xchacha`]. An implementation
-/// of this IETF flavor of XChaCha20Poly1305 can be found in
-/// [`chacha20poly1305::XChaCha20Poly1305`].
+/// construction as described in [`draft-irtf-cfrg-xchacha`], which is
+/// implemented by [`XChaCha20Poly1305Ietf`] compatibly with libsodium's
+/// `crypto_aead_xchacha20poly1305_ietf`.
 ///
 /// [`draft-irtf-cfrg-xchacha`]: https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-xchacha
-/// [`chacha20poly1305::XChaCha20Poly1305`]: https://docs.rs/chacha20poly1305/latest/chacha20poly1305/type.XChaCha20Poly1305.html
 #[cfg(feature = "chacha20")]
 pub type XChaCha20Poly1305 = SecretBox<ChaCha20>;
 
@@ -140,12 +203,17 @@ pub type XSalsa20Poly1305 = SecretBox<Salsa20>;
 
 /// The NaCl `crypto_secretbox` authenticated symmetric encryption primitive,
 /// generic
+///
+/// Poly1305 is not key-committing: a ciphertext can be crafted to decrypt
+/// under more than one key. See [`CommittingSecretBox`] if this matters.
 pub struct SecretBox<C> {
     /// Secret key.
-    key: Key,
+    key: SecretKey,
 
     /// Cipher.
-    cipher: PhantomData<C>,
+    ///
+    /// No cipher instance is stored, so this shouldn't affect auto traits.
+    cipher: PhantomData<fn() -> C>,
 }
 
 impl<C> SecretBox<C> {
@@ -157,6 +225,16 @@ impl<C> SecretBox<C> {
 
     /// Size of a Poly1305 tag in bytes
     pub const TAG_SIZE: usize = 16;
+
+    /// Borrow the [`SecretKey`] of this [`SecretBox`].
+    ///
+    /// # ⚠️Warning
+    ///
+    /// The returned key is secret key material. Please treat it with the care
+    /// it deserves!
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.key
+    }
 }
 
 impl<C> SecretBox<C>
@@ -165,9 +243,21 @@ where
 {
     /// Initialize cipher instance and Poly1305 MAC.
     fn init_cipher_and_mac(&self, nonce: &Nonce) -> (C, Poly1305) {
-        let (nonce_prefix, nonce_suffix) = nonce.split_at(16);
-        let subkey = Zeroizing::new(C::kdf(&self.key, nonce_prefix.as_ref().into()));
-        let mut cipher = C::new(&subkey, nonce_suffix.as_ref().into());
+        let subkey = self.subkey(nonce);
+        Self::init_cipher_and_mac_with_subkey(&subkey, nonce)
+    }
+
+    /// Derive the subkey for the given nonce from its first 16 bytes.
+    fn subkey(&self, nonce: &Nonce) -> Zeroizing<Key> {
+        let nonce_prefix = GenericArray::from_slice(&nonce[..16]);
+        Zeroizing::new(C::kdf(self.key.as_ref(), nonce_prefix))
+    }
+
+    /// Initialize cipher instance and Poly1305 MAC from the subkey derived
+    /// for the given nonce.
+    fn init_cipher_and_mac_with_subkey(subkey: &Key, nonce: &Nonce) -> (C, Poly1305) {
+        let nonce_suffix = GenericArray::from_slice(&nonce[16..]);
+        let mut cipher = C::new(subkey, nonce_suffix);
 
         // Derive Poly1305 key from the first 32-bytes of the keystream.
         let mut mac_key = Zeroizing::new(poly1305::Key::default());
@@ -176,13 +266,85 @@ where
         let mac = Poly1305::new(&mac_key);
         (cipher, mac)
     }
+
+    /// Encrypt the plaintext in `buffer[TAG_SIZE..]` in-place, writing the
+    /// Poly1305 tag into the first [`SecretBox::TAG_SIZE`] bytes.
+    ///
+    /// The caller reserves space for the tag at the front of the buffer, so
+    /// the result has the same layout as libsodium's `crypto_secretbox_easy`
+    /// without having to move the message within the buffer.
+    ///
+    /// Returns [`Error`] if `buffer` is shorter than [`SecretBox::TAG_SIZE`].
+    pub fn encrypt_in_place_prefixed(&self, nonce: &Nonce, buffer: &mut [u8]) -> Result<(), Error> {
+        if buffer.len() < Self::TAG_SIZE {
+            return Err(Error);
+        }
+
+        let (tag, body) = buffer.split_at_mut(Self::TAG_SIZE);
+        let computed_tag = self.encrypt_in_place_detached(nonce, b"", body)?;
+        tag.copy_from_slice(&computed_tag);
+        Ok(())
+    }
+
+    /// Decrypt a buffer laid out as a Poly1305 tag followed by the ciphertext
+    /// in-place, returning the decrypted message, i.e. `buffer[TAG_SIZE..]`.
+    ///
+    /// This is the inverse of [`SecretBox::encrypt_in_place_prefixed`]. The
+    /// tag bytes at the front of the buffer are left untouched.
+    pub fn decrypt_in_place_prefixed<'a>(
+        &self,
+        nonce: &Nonce,
+        buffer: &'a mut [u8],
+    ) -> Result<&'a mut [u8], Error> {
+        if buffer.len() < Self::TAG_SIZE {
+            return Err(Error);
+        }
+
+        let (tag, body) = buffer.split_at_mut(Self::TAG_SIZE);
+        self.decrypt_in_place_detached(nonce, b"", body, Tag::from_slice(tag))?;
+        Ok(body)
+    }
+
+    /// Check the Poly1305 tag of a ciphertext laid out as the tag followed by
+    /// the encrypted message, like `crypto_secretbox_easy`, without
+    /// decrypting it.
+    ///
+    /// See [`SecretBox::verify_detached`] for more information.
+    pub fn verify(&self, nonce: &Nonce, ciphertext: &[u8]) -> Result<(), Error> {
+        if ciphertext.len() < Self::TAG_SIZE {
+            return Err(Error);
+        }
+
+        let (tag, body) = ciphertext.split_at(Self::TAG_SIZE);
+        self.verify_detached(nonce, body, Tag::from_slice(tag))
+    }
+
+    /// Check the Poly1305 tag of a ciphertext without decrypting it.
+    ///
+    /// Only the Poly1305 key is derived from the keystream, and the tags are
+    /// compared in constant time. Returns [`Error`] if the tag is invalid.
+    pub fn verify_detached(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        let (_, mac) = self.init_cipher_and_mac(nonce);
+        let expected_tag = mac.compute_unpadded(ciphertext);
+
+        if expected_tag.ct_eq(tag).into() {
+            Ok(())
+        } else {
+            Err(Error)
+        }
+    }
 }
 
 // Handwritten instead of derived to avoid `C: Clone` bound
 impl<C> Clone for SecretBox<C> {
     fn clone(&self) -> Self {
         Self {
-            key: self.key,
+            key: self.key.clone(),
             cipher: PhantomData,
         }
     }
@@ -194,10 +356,7 @@ impl<C> KeySizeUser for SecretBox<C> {
 
 impl<C> KeyInit for SecretBox<C> {
     fn new(key: &Key) -> Self {
-        SecretBox {
-            key: *key,
-            cipher: PhantomData,
-        }
+        SecretKey::from(*key).into()
     }
 }
 
@@ -217,22 +376,20 @@ where
         associated_data: &[u8],
         buffer: &mut dyn Buffer,
     ) -> Result<(), Error> {
+        // AAD unsupported
+        if !associated_data.is_empty() {
+            return Err(Error);
+        }
+
         let pt_len = buffer.len();
 
         // Make room in the buffer for the tag. It needs to be prepended.
+        // `Buffer` can only grow at the end, so the message has to be moved;
+        // use `encrypt_in_place_prefixed` directly to avoid this copy.
         buffer.extend_from_slice(Tag::default().as_slice())?;
-
-        // TODO(tarcieri): add offset param to `encrypt_in_place_detached`
         buffer.as_mut().copy_within(..pt_len, Self::TAG_SIZE);
 
-        let tag = self.encrypt_in_place_detached(
-            nonce,
-            associated_data,
-            &mut buffer.as_mut()[Self::TAG_SIZE..],
-        )?;
-
-        buffer.as_mut()[..Self::TAG_SIZE].copy_from_slice(tag.as_slice());
-        Ok(())
+        self.encrypt_in_place_prefixed(nonce, buffer.as_mut())
     }
 
     fn encrypt_in_place_detached(
@@ -257,22 +414,17 @@ where
         associated_data: &[u8],
         buffer: &mut dyn Buffer,
     ) -> Result<(), Error> {
-        if buffer.len() < Self::TAG_SIZE {
+        // AAD unsupported
+        if !associated_data.is_empty() {
             return Err(Error);
         }
 
-        let tag = Tag::clone_from_slice(&buffer.as_ref()[..Self::TAG_SIZE]);
+        let pt_len = self
+            .decrypt_in_place_prefixed(nonce, buffer.as_mut())?
+            .len();
 
-        self.decrypt_in_place_detached(
-            nonce,
-            associated_data,
-            &mut buffer.as_mut()[Self::TAG_SIZE..],
-            &tag,
-        )?;
-
-        let pt_len = buffer.len() - Self::TAG_SIZE;
-
-        // TODO(tarcieri): add offset param to `encrypt_in_place_detached`
+        // `Buffer` can only be truncated at the end, so the message has to be
+        // moved; use `decrypt_in_place_prefixed` directly to avoid this copy.
         buffer.as_mut().copy_within(Self::TAG_SIZE.., 0);
         buffer.truncate(pt_len);
         Ok(())
@@ -294,7 +446,6 @@ where
         let expected_tag = mac.compute_unpadded(buffer);
 
         // This performs a constant-time comparison using the `subtle` crate
-        use subtle::ConstantTimeEq;
         if expected_tag.ct_eq(tag).into() {
             cipher.apply_keystream(buffer);
             Ok(())
@@ -304,12 +455,6 @@ where
     }
 }
 
-impl<C> Drop for SecretBox<C> {
-    fn drop(&mut self) {
-        self.key.as_mut_slice().zeroize();
-    }
-}
-
 /// Key derivation function: trait for abstracting over HSalsa20 and HChaCha20.
 pub trait Kdf {
     /// Derive a new key from the provided input key and nonce.
@@ -0,0 +1,175 @@
+//! Nonce management utilities.
+//!
+//! A nonce must never be reused with the same key. This module provides
+//! helpers for the two common strategies of picking nonces:
+//!
+//! - [`generate`]: a random nonce for every message. The 192-bit nonce is
+//!   large enough for random nonces to be safe.
+//! - [`NonceSequence`]: a fixed (random) prefix combined with a message
+//!   counter, which can be checkpointed and resumed across restarts.
+
+use crate::{Error, Nonce};
+
+#[cfg(feature = "rand_core")]
+use aead::rand_core::CryptoRngCore;
+
+#[cfg(feature = "serde")]
+use serdect::serde::{de, ser, Deserialize, Serialize};
+
+/// Increment a little endian number in constant time, wrapping around on
+/// overflow.
+///
+/// This is equivalent to libsodium's `sodium_increment`.
+pub fn increment(n: &mut [u8]) {
+    let mut carry = 1u16;
+
+    for byte in n.iter_mut() {
+        carry += u16::from(*byte);
+        *byte = carry as u8;
+        carry >>= 8;
+    }
+}
+
+/// Generate a random nonce.
+#[cfg(feature = "rand_core")]
+pub fn generate(csprng: &mut impl CryptoRngCore) -> Nonce {
+    let mut nonce = Nonce::default();
+    csprng.fill_bytes(&mut nonce);
+    nonce
+}
+
+/// Sequence of nonces made up of a fixed prefix and a message counter.
+///
+/// Each nonce consists of the 64-bit counter in little endian byte order,
+/// followed by the 128-bit prefix:
+///
+/// ```text
+/// counter (8 bytes, little endian) || prefix (16 bytes)
+/// ```
+///
+/// Consecutive nonces are therefore related by [`increment`]. The counter
+/// never wraps around: once it's exhausted, [`NonceSequence::next_nonce`]
+/// returns an error.
+///
+/// To continue a sequence after a restart, persist a [`Checkpoint`] before
+/// using the nonces it covers, and call [`NonceSequence::resume`] with a gap
+/// at least as large as the number of nonces which may have been used since.
+#[derive(Debug, Eq, PartialEq)]
+pub struct NonceSequence {
+    prefix: [u8; NonceSequence::PREFIX_SIZE],
+    counter: u64,
+}
+
+impl NonceSequence {
+    /// Size of the nonce prefix in bytes.
+    pub const PREFIX_SIZE: usize = 16;
+
+    /// Create a new [`NonceSequence`] with the given prefix, starting at
+    /// counter zero.
+    ///
+    /// The prefix must be unique for the key the nonces are used with.
+    pub fn new(prefix: [u8; Self::PREFIX_SIZE]) -> Self {
+        Self { prefix, counter: 0 }
+    }
+
+    /// Create a new [`NonceSequence`] with a random prefix.
+    #[cfg(feature = "rand_core")]
+    pub fn generate(csprng: &mut impl CryptoRngCore) -> Self {
+        let mut prefix = [0u8; Self::PREFIX_SIZE];
+        csprng.fill_bytes(&mut prefix);
+        Self::new(prefix)
+    }
+
+    /// Resume a sequence from a [`Checkpoint`], skipping `gap` nonces.
+    ///
+    /// Returns [`Error`] if skipping the gap would exhaust the counter.
+    pub fn resume(checkpoint: &Checkpoint, gap: u64) -> Result<Self, Error> {
+        let (counter, prefix) = checkpoint.0.split_at(8);
+        let counter = u64::from_le_bytes(counter.try_into().unwrap());
+
+        Ok(Self {
+            prefix: prefix.try_into().unwrap(),
+            counter: counter.checked_add(gap).ok_or(Error)?,
+        })
+    }
+
+    /// Get the counter value which will be used for the next nonce.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Get the next nonce in the sequence.
+    ///
+    /// Returns [`Error`] if the counter is exhausted.
+    pub fn next_nonce(&mut self) -> Result<Nonce, Error> {
+        if self.counter == u64::MAX {
+            return Err(Error);
+        }
+
+        let mut nonce = Nonce::default();
+        nonce[..8].copy_from_slice(&self.counter.to_le_bytes());
+        nonce[8..].copy_from_slice(&self.prefix);
+        self.counter += 1;
+        Ok(nonce)
+    }
+
+    /// Save the current state of the sequence.
+    pub fn checkpoint(&self) -> Checkpoint {
+        let mut bytes = [0u8; Checkpoint::BYTES];
+        bytes[..8].copy_from_slice(&self.counter.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.prefix);
+        Checkpoint(bytes)
+    }
+}
+
+/// Saved state of a [`NonceSequence`].
+///
+/// Serialized as the next nonce of the sequence, i.e. the counter in little
+/// endian byte order followed by the prefix.
+///
+/// This type can be serialized if the `serde` feature is enabled.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Checkpoint([u8; Checkpoint::BYTES]);
+
+impl Checkpoint {
+    /// Size of a serialized checkpoint in bytes.
+    pub const BYTES: usize = 24;
+
+    /// Initialize [`Checkpoint`] from a byte array.
+    pub fn from_bytes(bytes: [u8; Self::BYTES]) -> Self {
+        Self(bytes)
+    }
+
+    /// Serialize this checkpoint as bytes.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        self.0
+    }
+}
+
+impl From<[u8; Checkpoint::BYTES]> for Checkpoint {
+    fn from(bytes: [u8; Checkpoint::BYTES]) -> Checkpoint {
+        Self::from_bytes(bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Checkpoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serdect::array::serialize_hex_upper_or_bin(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Checkpoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut bytes = [0u8; Self::BYTES];
+        serdect::array::deserialize_hex_or_bin(&mut bytes, deserializer)?;
+        Ok(Checkpoint(bytes))
+    }
+}
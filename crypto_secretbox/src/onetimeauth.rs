@@ -0,0 +1,112 @@
+//! One-time authentication using Poly1305, i.e. NaCl's `crypto_onetimeauth`.
+//!
+//! This is the authenticator [`SecretBox`] uses internally, keyed with the
+//! first 32 bytes of the keystream.
+//!
+//! # ⚠️ Security Warning
+//!
+//! As the name suggests, a key must only ever be used to authenticate a
+//! single message. Authenticating two different messages under the same key
+//! allows an attacker to forge tags for other messages.
+//!
+//! [`SecretBox`]: crate::SecretBox
+
+use crate::{Error, KeyInit, Tag};
+use poly1305::{universal_hash::UniversalHash, Block, Poly1305, BLOCK_SIZE};
+use subtle::ConstantTimeEq;
+
+pub use poly1305::Key;
+
+/// Size of a one-time authentication key in bytes.
+pub const KEY_SIZE: usize = 32;
+
+/// Size of a one-time authentication tag in bytes.
+pub const TAG_SIZE: usize = 16;
+
+/// Compute the tag for the given message.
+///
+/// This is equivalent to libsodium's `crypto_onetimeauth`.
+pub fn auth(key: &Key, message: &[u8]) -> Tag {
+    Poly1305::new(key).compute_unpadded(message)
+}
+
+/// Check the given tag for the given message in constant time.
+///
+/// This is equivalent to libsodium's `crypto_onetimeauth_verify`.
+pub fn verify(key: &Key, message: &[u8], tag: &Tag) -> Result<(), Error> {
+    let mut state = State::new(key);
+    state.update(message);
+    state.verify(tag)
+}
+
+/// Incremental one-time authenticator, for messages which are fed to it in
+/// arbitrarily sized pieces.
+///
+/// This is equivalent to libsodium's `crypto_onetimeauth_state`, with
+/// [`State::new`], [`State::update`] and [`State::finalize`] corresponding to
+/// `crypto_onetimeauth_init`, `crypto_onetimeauth_update` and
+/// `crypto_onetimeauth_final` respectively.
+#[derive(Clone)]
+pub struct State {
+    mac: Poly1305,
+    buffer: Block,
+    pos: usize,
+}
+
+impl State {
+    /// Start authenticating a message under the given key.
+    pub fn new(key: &Key) -> Self {
+        Self::from_mac(Poly1305::new(key))
+    }
+
+    /// Start authenticating a message using an initialized Poly1305 instance.
+    pub(crate) fn from_mac(mac: Poly1305) -> Self {
+        Self {
+            mac,
+            buffer: Block::default(),
+            pos: 0,
+        }
+    }
+
+    /// Authenticate the next piece of the message.
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.pos > 0 {
+            let len = data.len().min(BLOCK_SIZE - self.pos);
+            self.buffer[self.pos..][..len].copy_from_slice(&data[..len]);
+            self.pos += len;
+            data = &data[len..];
+
+            if self.pos < BLOCK_SIZE {
+                return;
+            }
+
+            self.mac.update(&[self.buffer]);
+            self.pos = 0;
+        }
+
+        let (blocks, rest) = data.split_at(data.len() - data.len() % BLOCK_SIZE);
+
+        for block in blocks.chunks_exact(BLOCK_SIZE) {
+            self.mac.update(&[*Block::from_slice(block)]);
+        }
+
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.pos = rest.len();
+    }
+
+    /// Finish authenticating the message, returning the tag.
+    pub fn finalize(self) -> Tag {
+        // The trailing partial block (if any) is handled by `compute_unpadded`
+        self.mac.compute_unpadded(&self.buffer[..self.pos])
+    }
+
+    /// Finish authenticating the message, and check the result against the
+    /// given tag in constant time.
+    pub fn verify(self, tag: &Tag) -> Result<(), Error> {
+        if self.finalize().ct_eq(tag).into() {
+            Ok(())
+        } else {
+            Err(Error)
+        }
+    }
+}
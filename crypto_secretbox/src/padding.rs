@@ -0,0 +1,237 @@
+//! Length-hiding padding, i.e. libsodium's `sodium_pad` and `sodium_unpad`.
+//!
+//! Messages are padded using the ISO/IEC 7816-4 scheme: a `0x80` marker byte
+//! is appended, followed by as many zero bytes as needed to reach the padded
+//! length. At least one byte of padding is always added, so padding can
+//! always be removed unambiguously.
+//!
+//! The padded length is determined by a [`Padding`] policy: either the next
+//! multiple of a fixed block size, as in libsodium, or the [PADMÉ] bucket
+//! scheme, which leaks at most O(log log n) bits of the length while keeping
+//! the overhead below 12%.
+//!
+//! Padding is removed in constant time with respect to the contents of the
+//! buffer, so it can be stripped from decrypted messages without revealing
+//! the unpadded length through timing.
+//!
+//! [PADMÉ]: https://lbarman.ch/blog/padme/
+
+use crate::{Error, Kdf, Nonce, SecretBox};
+use aead::{
+    consts::{U32, U8},
+    AeadInPlace, Buffer,
+};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+use core::cmp;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Marker byte which starts the padding.
+const MARKER: u8 = 0x80;
+
+/// Padding policy which determines the padded length of a message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Padding {
+    /// Pad to the next multiple of the given block size, like `sodium_pad`.
+    ///
+    /// A full block of padding is added if the message length is already a
+    /// multiple of the block size.
+    Block(usize),
+
+    /// Pad to the next [PADMÉ] bucket.
+    ///
+    /// [PADMÉ]: https://lbarman.ch/blog/padme/
+    Padme,
+}
+
+impl Padding {
+    /// Get the padded length of a message of `len` bytes.
+    ///
+    /// Returns `None` if the block size is zero or the padded length
+    /// overflows.
+    pub fn padded_len(self, len: usize) -> Option<usize> {
+        match self {
+            Padding::Block(block_size) => len
+                .checked_div(block_size)?
+                .checked_add(1)?
+                .checked_mul(block_size),
+            Padding::Padme => padme(len.checked_add(1)?),
+        }
+    }
+
+    /// Get the number of trailing bytes of a padded message of `len` bytes
+    /// which may contain padding.
+    fn max_padding(self, len: usize) -> Result<usize, Error> {
+        match self {
+            Padding::Block(block_size) if block_size == 0 || len < block_size => Err(Error),
+            Padding::Block(block_size) => Ok(block_size),
+            Padding::Padme => Ok(len),
+        }
+    }
+}
+
+/// Pad the message in `buffer` in-place according to the given policy.
+///
+/// This is compatible with libsodium's `sodium_pad` when using
+/// [`Padding::Block`].
+///
+/// Returns [`Error`] if the block size is zero or `buffer` has insufficient
+/// capacity, in which case `buffer` is left unmodified.
+pub fn pad(buffer: &mut dyn Buffer, padding: Padding) -> Result<(), Error> {
+    const ZEROS: [u8; 64] = [0; 64];
+
+    let len = buffer.len();
+    let padded_len = padding.padded_len(len).ok_or(Error)?;
+
+    let result = buffer.extend_from_slice(&[MARKER]).and_then(|()| {
+        while buffer.len() < padded_len {
+            let n = cmp::min(padded_len - buffer.len(), ZEROS.len());
+            buffer.extend_from_slice(&ZEROS[..n])?;
+        }
+        Ok(())
+    });
+
+    if result.is_err() {
+        buffer.truncate(len);
+    }
+
+    result
+}
+
+/// Remove the padding from the message in `buffer` in-place.
+///
+/// This is compatible with libsodium's `sodium_unpad` when using
+/// [`Padding::Block`].
+///
+/// Returns [`Error`] if the padding is invalid, in which case `buffer` is
+/// left unmodified.
+pub fn unpad(buffer: &mut dyn Buffer, padding: Padding) -> Result<(), Error> {
+    let len = unpadded_len(buffer.as_ref(), padding)?;
+    buffer.truncate(len);
+    Ok(())
+}
+
+/// Get the length of the message in the padded `buffer`.
+///
+/// The trailing bytes which may contain padding are scanned in constant time
+/// regardless of where the padding starts.
+///
+/// Returns [`Error`] if the padding is invalid.
+pub fn unpadded_len(buffer: &[u8], padding: Padding) -> Result<usize, Error> {
+    let max_padding = padding.max_padding(buffer.len())?;
+
+    let mut is_zero = Choice::from(1);
+    let mut found = Choice::from(0);
+    let mut padding_len = 0u64;
+
+    for (i, byte) in buffer.iter().rev().take(max_padding).enumerate() {
+        let is_marker = is_zero & byte.ct_eq(&MARKER);
+        padding_len.conditional_assign(&(i as u64 + 1), is_marker);
+        found |= is_marker;
+        is_zero &= byte.ct_eq(&0);
+    }
+
+    if found.into() {
+        Ok(buffer.len() - padding_len as usize)
+    } else {
+        Err(Error)
+    }
+}
+
+/// Round `len` up to the next PADMÉ bucket.
+fn padme(len: usize) -> Option<usize> {
+    if len < 2 {
+        return Some(len);
+    }
+
+    // The length is rounded to keep only the top `log2(log2(len)) + 1` bits
+    let exponent = usize::BITS - 1 - len.leading_zeros();
+    let mantissa_bits = u32::BITS - exponent.leading_zeros();
+    let mask = (1 << (exponent - mantissa_bits)) - 1;
+
+    len.checked_add(mask).map(|len| len & !mask)
+}
+
+impl<C> SecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Pad the plaintext in `buffer` according to the given policy, then
+    /// encrypt it in-place.
+    ///
+    /// `buffer` needs enough capacity for the padding as well as the tag.
+    /// Returns [`Error`] if it doesn't, in which case `buffer` is left
+    /// unmodified.
+    pub fn encrypt_padded_in_place(
+        &self,
+        nonce: &Nonce,
+        buffer: &mut dyn Buffer,
+        padding: Padding,
+    ) -> Result<(), Error> {
+        let len = buffer.len();
+        pad(buffer, padding)?;
+
+        let result = self.encrypt_in_place(nonce, b"", buffer);
+        if result.is_err() {
+            buffer.truncate(len);
+        }
+
+        result
+    }
+
+    /// Decrypt the ciphertext in `buffer` in-place, then remove the padding
+    /// from the plaintext.
+    ///
+    /// If the ciphertext is authentic but its padding is invalid, the
+    /// decrypted plaintext is zeroized and `buffer` is cleared before
+    /// returning [`Error`].
+    pub fn decrypt_padded_in_place(
+        &self,
+        nonce: &Nonce,
+        buffer: &mut dyn Buffer,
+        padding: Padding,
+    ) -> Result<(), Error> {
+        self.decrypt_in_place(nonce, b"", buffer)?;
+
+        let result = unpad(buffer, padding);
+        if result.is_err() {
+            buffer.as_mut().zeroize();
+            buffer.truncate(0);
+        }
+
+        result
+    }
+
+    /// Pad the plaintext according to the given policy and encrypt it,
+    /// returning the ciphertext.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_padded(
+        &self,
+        nonce: &Nonce,
+        plaintext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, Error> {
+        let padded_len = padding.padded_len(plaintext.len()).ok_or(Error)?;
+        let mut buffer = Vec::with_capacity(padded_len + Self::TAG_SIZE);
+        buffer.extend_from_slice(plaintext);
+        self.encrypt_padded_in_place(nonce, &mut buffer, padding)?;
+        Ok(buffer)
+    }
+
+    /// Decrypt the ciphertext and remove the padding, returning the
+    /// plaintext.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt_padded(
+        &self,
+        nonce: &Nonce,
+        ciphertext: &[u8],
+        padding: Padding,
+    ) -> Result<Vec<u8>, Error> {
+        let mut buffer = ciphertext.to_vec();
+        self.decrypt_padded_in_place(nonce, &mut buffer, padding)?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,228 @@
+//! Password-based encryption using Argon2id, compatible with libsodium's
+//! `crypto_pwhash`.
+//!
+//! A [`SecretBox`] key is derived from a password and a random salt with
+//! Argon2id v1.3, exactly like libsodium's `crypto_pwhash` with
+//! `crypto_pwhash_ALG_ARGON2ID13`. The cost of the derivation is set by
+//! [`Params`], which provides libsodium's presets.
+//!
+//! [`SecretBox::encrypt_with_password`] produces a self-describing blob:
+//!
+//! ```text
+//! version (1) || ops limit (4, LE) || mem limit in KiB (4, LE) || salt (16)
+//!     || nonce (24) || Poly1305 tag (16) || ciphertext
+//! ```
+//!
+//! where everything following the nonce is the output of
+//! `crypto_secretbox_easy` under the derived key. Data encrypted with
+//! libsodium's `crypto_pwhash` followed by `crypto_secretbox_easy` can be
+//! decrypted by prefixing it with the header, or by deriving the key with
+//! [`SecretBox::from_password`].
+
+use crate::{Error, Kdf, Nonce, SecretBox, SecretKey};
+use aead::{
+    consts::{U32, U8},
+    Aead,
+};
+use alloc::vec::Vec;
+use argon2::{Algorithm, Argon2, Version};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "rand_core")]
+use {crate::nonce, aead::rand_core::CryptoRngCore};
+
+/// Current version of the blob format.
+pub const VERSION: u8 = 1;
+
+/// Size of a salt in bytes.
+pub const SALT_SIZE: usize = 16;
+
+/// Size of the blob header in bytes.
+pub const HEADER_SIZE: usize = 1 + 4 + 4 + SALT_SIZE + 24;
+
+/// Argon2id salt.
+pub type Salt = [u8; SALT_SIZE];
+
+/// Argon2id cost parameters, i.e. libsodium's `opslimit` and `memlimit`.
+///
+/// The degree of parallelism is always 1, like libsodium.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Params {
+    /// Number of passes.
+    ops_limit: u32,
+
+    /// Memory size in KiB.
+    mem_limit_kib: u32,
+}
+
+impl Params {
+    /// Parameters for interactive use, i.e.
+    /// `crypto_pwhash_OPSLIMIT_INTERACTIVE` and
+    /// `crypto_pwhash_MEMLIMIT_INTERACTIVE` (2 passes, 64 MiB).
+    pub const INTERACTIVE: Self = Self {
+        ops_limit: 2,
+        mem_limit_kib: 64 * 1024,
+    };
+
+    /// Parameters for moderately sensitive data, i.e.
+    /// `crypto_pwhash_OPSLIMIT_MODERATE` and
+    /// `crypto_pwhash_MEMLIMIT_MODERATE` (3 passes, 256 MiB).
+    pub const MODERATE: Self = Self {
+        ops_limit: 3,
+        mem_limit_kib: 256 * 1024,
+    };
+
+    /// Parameters for highly sensitive data, i.e.
+    /// `crypto_pwhash_OPSLIMIT_SENSITIVE` and
+    /// `crypto_pwhash_MEMLIMIT_SENSITIVE` (4 passes, 1 GiB).
+    pub const SENSITIVE: Self = Self {
+        ops_limit: 4,
+        mem_limit_kib: 1024 * 1024,
+    };
+
+    /// Create custom parameters from a number of passes and a memory size in
+    /// bytes, like libsodium's `opslimit` and `memlimit`.
+    ///
+    /// As in libsodium, the memory size is rounded down to a whole number of
+    /// KiB. Returns [`Error`] if either is below libsodium's minimum for
+    /// Argon2id (1 pass and 8 KiB) or the memory size is too large.
+    pub fn new(ops_limit: u32, mem_limit: usize) -> Result<Self, Error> {
+        let mem_limit_kib = u32::try_from(mem_limit / 1024).map_err(|_| Error)?;
+        Self::from_kib(ops_limit, mem_limit_kib)
+    }
+
+    /// Get the number of passes.
+    pub fn ops_limit(&self) -> u32 {
+        self.ops_limit
+    }
+
+    /// Get the memory size in bytes.
+    pub fn mem_limit(&self) -> u64 {
+        u64::from(self.mem_limit_kib) * 1024
+    }
+
+    /// Create parameters from a number of passes and a memory size in KiB.
+    fn from_kib(ops_limit: u32, mem_limit_kib: u32) -> Result<Self, Error> {
+        if ops_limit < 1 || mem_limit_kib < 8 {
+            return Err(Error);
+        }
+
+        Ok(Self {
+            ops_limit,
+            mem_limit_kib,
+        })
+    }
+
+    /// Check whether these parameters use no more memory than `limit`, and
+    /// cost no more in total.
+    ///
+    /// Passes and memory can be traded for each other, so they aren't
+    /// limited separately: libsodium accepts e.g. more passes over less
+    /// memory than a preset.
+    fn within(&self, limit: &Self) -> bool {
+        self.mem_limit_kib <= limit.mem_limit_kib && self.cost() <= limit.cost()
+    }
+
+    /// Get the total cost, i.e. the number of passes times the memory size in
+    /// KiB.
+    fn cost(&self) -> u64 {
+        u64::from(self.ops_limit) * u64::from(self.mem_limit_kib)
+    }
+}
+
+/// Derive a [`SecretKey`] from a password and salt with Argon2id.
+///
+/// This is equivalent to libsodium's `crypto_pwhash` with an output length of
+/// [`SecretKey::BYTES`] and `crypto_pwhash_ALG_ARGON2ID13`.
+pub fn derive_key(password: &[u8], salt: &Salt, params: Params) -> Result<SecretKey, Error> {
+    let params = argon2::Params::new(
+        params.mem_limit_kib,
+        params.ops_limit,
+        1,
+        Some(SecretKey::BYTES),
+    )
+    .map_err(|_| Error)?;
+
+    let mut key = Zeroizing::new([0u8; SecretKey::BYTES]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password, salt, &mut *key)
+        .map_err(|_| Error)?;
+
+    Ok(SecretKey::from_bytes(*key))
+}
+
+impl<C> SecretBox<C> {
+    /// Create a new [`SecretBox`] with a key derived from a password and salt
+    /// with Argon2id.
+    ///
+    /// See [`derive_key`] for more information.
+    pub fn from_password(password: &[u8], salt: &Salt, params: Params) -> Result<Self, Error> {
+        derive_key(password, salt, params).map(Self::from)
+    }
+}
+
+impl<C> SecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Encrypt `plaintext` under a key derived from `password` with a random
+    /// salt, returning a blob which records the salt and cost parameters.
+    ///
+    /// Returns [`Error`] if `params` use more memory than
+    /// [`Params::SENSITIVE`] or cost more in total (passes times memory),
+    /// since [`SecretBox::decrypt_with_password`] would refuse to open the
+    /// blob.
+    #[cfg(feature = "rand_core")]
+    pub fn encrypt_with_password(
+        password: &[u8],
+        csprng: &mut impl CryptoRngCore,
+        plaintext: &[u8],
+        params: Params,
+    ) -> Result<Vec<u8>, Error> {
+        if !params.within(&Params::SENSITIVE) {
+            return Err(Error);
+        }
+
+        let mut salt = Salt::default();
+        csprng.fill_bytes(&mut salt);
+        let nonce = nonce::generate(csprng);
+
+        let ciphertext =
+            Self::from_password(password, &salt, params)?.encrypt(&nonce, plaintext)?;
+
+        let mut blob = Vec::with_capacity(HEADER_SIZE + ciphertext.len());
+        blob.push(VERSION);
+        blob.extend_from_slice(&params.ops_limit.to_le_bytes());
+        blob.extend_from_slice(&params.mem_limit_kib.to_le_bytes());
+        blob.extend_from_slice(&salt);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob produced by [`SecretBox::encrypt_with_password`] using
+    /// `password`.
+    ///
+    /// To bound the work an untrusted blob can demand, returns [`Error`] if
+    /// its cost parameters use more memory than [`Params::SENSITIVE`] or cost
+    /// more in total (passes times memory).
+    pub fn decrypt_with_password(password: &[u8], blob: &[u8]) -> Result<Vec<u8>, Error> {
+        if blob.len() < HEADER_SIZE || blob[0] != VERSION {
+            return Err(Error);
+        }
+
+        let (header, ciphertext) = blob.split_at(HEADER_SIZE);
+        let ops_limit = u32::from_le_bytes(header[1..5].try_into().unwrap());
+        let mem_limit_kib = u32::from_le_bytes(header[5..9].try_into().unwrap());
+        let salt = header[9..25].try_into().unwrap();
+        let nonce = Nonce::from_slice(&header[25..]);
+
+        let params = Params::from_kib(ops_limit, mem_limit_kib)?;
+        if !params.within(&Params::SENSITIVE) {
+            return Err(Error);
+        }
+
+        Self::from_password(password, salt, params)?.decrypt(nonce, ciphertext)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{Key, SecretBox};
+use core::{
+    array::TryFromSliceError,
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(feature = "rand_core")]
+use aead::rand_core::CryptoRngCore;
+
+#[cfg(feature = "serde")]
+use serdect::serde::{de, ser, Deserialize, Serialize};
+
+/// A `crypto_secretbox` secret key.
+///
+/// The key is zeroized when dropped, and compared in constant time.
+///
+/// This type can be serialized if the `serde` feature is enabled.
+#[derive(Clone)]
+pub struct SecretKey(Key);
+
+impl SecretKey {
+    /// Size of a secret key in bytes.
+    pub const BYTES: usize = 32;
+
+    /// Initialize [`SecretKey`] from a byte array.
+    pub fn from_bytes(bytes: [u8; Self::BYTES]) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Initialize [`SecretKey`] from a byte slice.
+    ///
+    /// Returns [`TryFromSliceError`] if the slice length is not exactly equal
+    /// to [`SecretKey::BYTES`].
+    pub fn from_slice(slice: &[u8]) -> Result<Self, TryFromSliceError> {
+        slice.try_into().map(Self::from_bytes)
+    }
+
+    /// Generate a random [`SecretKey`].
+    #[cfg(feature = "rand_core")]
+    pub fn generate(csprng: &mut impl CryptoRngCore) -> Self {
+        let mut key = Key::default();
+        csprng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Borrow the secret key as bytes.
+    ///
+    /// # ⚠️Warning
+    ///
+    /// The returned bytes are secret key material. Please treat them with
+    /// the care they deserve!
+    pub fn as_bytes(&self) -> &[u8; Self::BYTES] {
+        self.0.as_ref()
+    }
+}
+
+impl AsRef<Key> for SecretKey {
+    fn as_ref(&self) -> &Key {
+        &self.0
+    }
+}
+
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKey").finish_non_exhaustive()
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.0.as_mut_slice().zeroize();
+    }
+}
+
+impl Eq for SecretKey {}
+
+impl From<[u8; SecretKey::BYTES]> for SecretKey {
+    fn from(bytes: [u8; SecretKey::BYTES]) -> SecretKey {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl From<Key> for SecretKey {
+    fn from(key: Key) -> SecretKey {
+        Self(key)
+    }
+}
+
+impl<C> From<SecretKey> for SecretBox<C> {
+    fn from(key: SecretKey) -> SecretBox<C> {
+        SecretBox {
+            key,
+            cipher: PhantomData,
+        }
+    }
+}
+
+impl<C> From<&SecretKey> for SecretBox<C> {
+    fn from(key: &SecretKey) -> SecretBox<C> {
+        key.clone().into()
+    }
+}
+
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl TryFrom<&[u8]> for SecretKey {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[u8]) -> Result<Self, TryFromSliceError> {
+        Self::from_slice(slice)
+    }
+}
+
+impl ZeroizeOnDrop for SecretKey {}
+
+#[cfg(feature = "serde")]
+impl Serialize for SecretKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serdect::array::serialize_hex_upper_or_bin(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SecretKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut key = SecretKey(Key::default());
+        serdect::array::deserialize_hex_or_bin(&mut key.0, deserializer)?;
+        Ok(key)
+    }
+}
@@ -0,0 +1,342 @@
+//! Chunked encrypted file format supporting random access reads.
+//!
+//! Files are split into fixed-size chunks which are each encrypted with
+//! [`SecretBox`], using the [`StreamBE32`] nonce construction: a random
+//! per-file nonce prefix followed by the chunk index and a flag marking the
+//! last chunk. Reading a range of bytes from the middle of a file only
+//! requires decrypting the chunks it touches, while reordered, duplicated or
+//! truncated chunks are still detected.
+//!
+//! # File layout
+//!
+//! ```text
+//! version (1 byte) || chunk size (4 bytes, little endian) || nonce prefix (19 bytes)
+//! || chunk 0 || chunk 1 || ... || last chunk
+//! ```
+//!
+//! Each chunk consists of a Poly1305 tag followed by the ciphertext, as
+//! produced by [`SecretBox`]. Every chunk except the last one contains
+//! exactly `chunk size` bytes of plaintext, and the last chunk contains the
+//! remaining (possibly zero) bytes, i.e. the chunks following the header are
+//! exactly the output of a [`StreamWriter`] keyed with the per-file key
+//! described below. This makes it possible to locate the last chunk from the
+//! length of the file.
+//!
+//! The chunks aren't encrypted under the key directly, but under a per-file
+//! key derived from it and the whole header, so modifying any part of the
+//! header causes every chunk to fail to decrypt. Since the last chunk is
+//! always authenticated when a file is opened, a modified header is rejected
+//! up front.
+
+use crate::{
+    stream::{invalid_data, read_full, NoncePrefixBE32, StreamBE32, StreamWriter},
+    Error, Kdf, SecretBox, SecretKey,
+};
+use aead::{
+    consts::{U32, U8},
+    generic_array::GenericArray,
+    stream::{NewStream, StreamPrimitive},
+};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher};
+use std::{
+    io::{self, Read, Seek, SeekFrom, Write},
+    vec::Vec,
+};
+use zeroize::Zeroizing;
+
+/// Version of the file format.
+pub const VERSION: u8 = 1;
+
+/// Size of the file header in bytes.
+pub const HEADER_SIZE: usize = 24;
+
+/// Input to the [`Kdf`] used to derive the key the header is bound to.
+const HEADER_KEY_LABEL: &[u8; 16] = b"crypto_sbseekhdr";
+
+/// [`Write`] adapter which encrypts everything written to it into the
+/// seekable file format.
+///
+/// [`SeekableWriter::finish`] must be called to write the last chunk.
+/// Dropping the writer without calling it results in a truncated file,
+/// which will be rejected when decrypting.
+pub struct SeekableWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    writer: StreamWriter<C, W>,
+}
+
+impl<C, W> SeekableWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    /// Create a new [`SeekableWriter`], writing the file header to `inner`.
+    ///
+    /// The nonce prefix must be unique for every file encrypted under the
+    /// same key, and should be generated randomly.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn new(
+        secretbox: SecretBox<C>,
+        nonce: &NoncePrefixBE32<C>,
+        chunk_size: u32,
+        mut inner: W,
+    ) -> io::Result<Self> {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        let mut header = [0u8; HEADER_SIZE];
+        header[0] = VERSION;
+        header[1..5].copy_from_slice(&chunk_size.to_le_bytes());
+        header[5..].copy_from_slice(nonce);
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            writer: StreamWriter::new(
+                file_secretbox(&secretbox, &header),
+                nonce,
+                chunk_size as usize,
+                inner,
+            ),
+        })
+    }
+
+    /// Encrypt and write the last chunk, returning the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        self.writer.finish()
+    }
+}
+
+impl<C, W> Write for SeekableWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// [`Read`] and [`Seek`] adapter which decrypts a file written by
+/// [`SeekableWriter`].
+///
+/// Seeking is cheap: chunks are only read and decrypted once data from them
+/// is read. Chunks are authenticated before any of their plaintext is
+/// returned, and the last chunk is authenticated when the reader is created
+/// so truncated files are always rejected.
+pub struct SeekableReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read + Seek,
+{
+    stream: StreamBE32<C>,
+    inner: R,
+    chunk_size: u64,
+    last_chunk: u32,
+    len: u64,
+    position: u64,
+
+    /// Plaintext of the most recently decrypted chunk.
+    buffer: Vec<u8>,
+
+    /// Index of the chunk in `buffer`, if any.
+    buffered_chunk: Option<u32>,
+}
+
+impl<C, R> SeekableReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read + Seek,
+{
+    /// Create a new [`SeekableReader`], reading the file header from `inner`
+    /// and authenticating the last chunk.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the header is invalid, or
+    /// if the file is truncated.
+    pub fn new(secretbox: SecretBox<C>, mut inner: R) -> io::Result<Self> {
+        inner.seek(SeekFrom::Start(0))?;
+
+        let mut header = [0u8; HEADER_SIZE];
+        if read_full(&mut inner, &mut header)? != HEADER_SIZE || header[0] != VERSION {
+            return Err(invalid_data(Error));
+        }
+
+        let chunk_size = u32::from_le_bytes(header[1..5].try_into().unwrap());
+        if chunk_size == 0 {
+            return Err(invalid_data(Error));
+        }
+
+        let chunk_size = u64::from(chunk_size);
+        let segment_size = chunk_size + SecretBox::<C>::TAG_SIZE as u64;
+        let body_len = inner
+            .seek(SeekFrom::End(0))?
+            .checked_sub(HEADER_SIZE as u64)
+            .ok_or_else(|| invalid_data(Error))?;
+
+        // The last chunk is always present, and shorter than a full segment
+        let remainder = body_len % segment_size;
+        if remainder < SecretBox::<C>::TAG_SIZE as u64 {
+            return Err(invalid_data(Error));
+        }
+
+        let full_chunks = body_len / segment_size;
+        let last_chunk = u32::try_from(full_chunks).map_err(|_| invalid_data(Error))?;
+        let len = full_chunks * chunk_size + remainder - SecretBox::<C>::TAG_SIZE as u64;
+
+        let mut reader = Self {
+            stream: StreamBE32::from_aead(
+                file_secretbox(&secretbox, &header),
+                NoncePrefixBE32::<C>::from_slice(&header[5..]),
+            ),
+            inner,
+            chunk_size,
+            last_chunk,
+            len,
+            position: 0,
+            buffer: Vec::new(),
+            buffered_chunk: None,
+        };
+
+        reader.load_chunk(last_chunk)?;
+        Ok(reader)
+    }
+
+    /// Get the length of the decrypted file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Is the decrypted file empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read and decrypt the given chunk into the buffer, unless it's already
+    /// there.
+    fn load_chunk(&mut self, chunk: u32) -> io::Result<()> {
+        if self.buffered_chunk == Some(chunk) {
+            return Ok(());
+        }
+
+        self.buffered_chunk = None;
+
+        let segment_size = self.chunk_size + SecretBox::<C>::TAG_SIZE as u64;
+        let plaintext_len = if chunk == self.last_chunk {
+            self.len - u64::from(chunk) * self.chunk_size
+        } else {
+            self.chunk_size
+        };
+
+        // Lengths are bounded by the chunk size, which is a `u32`
+        let ciphertext_len = (plaintext_len as usize) + SecretBox::<C>::TAG_SIZE;
+        self.buffer.resize(ciphertext_len, 0);
+
+        self.inner.seek(SeekFrom::Start(
+            HEADER_SIZE as u64 + u64::from(chunk) * segment_size,
+        ))?;
+
+        let result = if read_full(&mut self.inner, &mut self.buffer)? == ciphertext_len {
+            let last = chunk == self.last_chunk;
+            self.stream
+                .decrypt_in_place(chunk, last, b"", &mut self.buffer)
+        } else {
+            // The file has changed since the reader was created
+            Err(Error)
+        };
+
+        if let Err(e) = result {
+            // Never hand out plaintext from a chunk which failed to
+            // authenticate
+            self.buffer.clear();
+            return Err(invalid_data(e));
+        }
+
+        self.buffered_chunk = Some(chunk);
+        Ok(())
+    }
+}
+
+impl<C, R> Read for SeekableReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        // Bounded by `last_chunk`, which is a `u32`
+        let chunk = (self.position / self.chunk_size) as u32;
+        self.load_chunk(chunk)?;
+
+        let offset = (self.position % self.chunk_size) as usize;
+        let len = buf.len().min(self.buffer.len() - offset);
+        buf[..len].copy_from_slice(&self.buffer[offset..][..len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<C, R> Seek for SeekableReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => add_offset(self.len, offset),
+            SeekFrom::Current(offset) => add_offset(self.position, offset),
+        };
+
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+}
+
+/// Derive the [`SecretBox`] used to encrypt the chunks of a file from the
+/// key and the file header.
+fn file_secretbox<C: Kdf>(secretbox: &SecretBox<C>, header: &[u8; HEADER_SIZE]) -> SecretBox<C> {
+    let header_key = Zeroizing::new(C::kdf(
+        secretbox.secret_key().as_ref(),
+        GenericArray::from_slice(HEADER_KEY_LABEL),
+    ));
+
+    // The header is longer than the input to the `Kdf`, so it's absorbed in
+    // two steps. Its length is fixed, so zero padding the second half is
+    // unambiguous.
+    let (first, second) = header.split_at(16);
+    let mut input = [0u8; 16];
+    input[..second.len()].copy_from_slice(second);
+
+    let intermediate = Zeroizing::new(C::kdf(&header_key, GenericArray::from_slice(first)));
+    SecretKey::from(C::kdf(&intermediate, GenericArray::from_slice(&input))).into()
+}
+
+/// Add a signed offset to a position, checking for overflow.
+fn add_offset(position: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        position.checked_add(offset as u64)
+    } else {
+        position.checked_sub(offset.unsigned_abs())
+    }
+}
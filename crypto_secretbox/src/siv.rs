@@ -0,0 +1,290 @@
+//! Nonce-misuse-resistant variant of [`SecretBox`] using synthetic nonces.
+//!
+//! [`SivSecretBox`] doesn't take a nonce from the caller. Instead, the nonce
+//! is derived deterministically from the message and an optional context
+//! string using a keyed hash, and is included in the output. When opening a
+//! message, the nonce is recomputed from the decrypted message and checked
+//! against the one it was encrypted under.
+//!
+//! Encrypting the same message under the same key and context always
+//! produces the same output, so an observer can tell when identical messages
+//! are sent. Nothing else is revealed, which makes this mode suitable for
+//! writers which can't guarantee nonce uniqueness. Where unique nonces are
+//! available, [`SecretBox`] should be preferred.
+//!
+//! # Construction
+//!
+//! Three keys are derived from the key using the cipher's [`Kdf`] (HSalsa20
+//! or HChaCha20), with 16-byte ASCII labels as input:
+//!
+//! ```text
+//! enc_key = Kdf(key, "crypto_sbsiv_enc")
+//! mac_key = Kdf(key, "crypto_sbsiv_mac")
+//! prf_key = Kdf(key, "crypto_sbsiv_prf")
+//! ```
+//!
+//! The synthetic nonce is computed from the context and message as follows,
+//! where `pad16` zero-pads its input to a multiple of 16 bytes and `le64`
+//! encodes a length as a 64-bit little endian integer:
+//!
+//! ```text
+//! h     = Poly1305(mac_key, pad16(context) || pad16(message) ||
+//!                           le64(len(context)) || le64(len(message)))
+//! nonce = Kdf(prf_key, h)[..24]
+//! ```
+//!
+//! The message is then encrypted with [`SecretBox`] under `enc_key` and the
+//! synthetic nonce.
+//!
+//! # Output layout
+//!
+//! ```text
+//! synthetic nonce (24 bytes) || Poly1305 tag (16 bytes) || ciphertext
+//! ```
+//!
+//! i.e. the nonce followed by the output of
+//! [`SecretBox::encrypt_in_place_prefixed`].
+
+use crate::{Error, Kdf, Key, Nonce, SecretBox, SecretKey, Tag};
+use aead::{
+    consts::{U32, U8},
+    generic_array::GenericArray,
+    Buffer, KeyInit, KeySizeUser,
+};
+use cipher::{IvSizeUser, KeyIvInit, StreamCipher};
+use poly1305::{universal_hash::UniversalHash, Poly1305};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Nonce-misuse-resistant `crypto_secretbox` instantiated with the XChaCha20
+/// stream cipher.
+#[cfg(feature = "chacha20")]
+pub type XChaCha20Poly1305Siv = SivSecretBox<chacha20::ChaCha20Legacy>;
+
+/// Nonce-misuse-resistant `crypto_secretbox` instantiated with the XSalsa20
+/// stream cipher.
+#[cfg(feature = "salsa20")]
+pub type XSalsa20Poly1305Siv = SivSecretBox<salsa20::Salsa20>;
+
+/// Inputs to the [`Kdf`] used to derive the keys of a [`SivSecretBox`].
+const ENC_KEY_LABEL: &[u8; 16] = b"crypto_sbsiv_enc";
+const MAC_KEY_LABEL: &[u8; 16] = b"crypto_sbsiv_mac";
+const PRF_KEY_LABEL: &[u8; 16] = b"crypto_sbsiv_prf";
+
+/// Size of the synthetic nonce in bytes.
+const NONCE_SIZE: usize = 24;
+
+/// Size of a Poly1305 tag in bytes.
+const TAG_SIZE: usize = 16;
+
+/// Size of the synthetic nonce and tag prepended to the message in bytes.
+const OVERHEAD: usize = NONCE_SIZE + TAG_SIZE;
+
+/// [`SecretBox`] with a synthetic nonce derived from the message, which makes
+/// it resistant to nonce misuse.
+///
+/// See the [module-level documentation](self) for the construction and
+/// output layout.
+pub struct SivSecretBox<C> {
+    /// [`SecretBox`] keyed with the encryption key.
+    secretbox: SecretBox<C>,
+
+    /// Poly1305 key used to hash the context and message.
+    mac_key: Zeroizing<poly1305::Key>,
+
+    /// Key used to derive the synthetic nonce from the hash.
+    prf_key: Zeroizing<Key>,
+}
+
+impl<C> SivSecretBox<C> {
+    /// Size of a key in bytes
+    pub const KEY_SIZE: usize = 32;
+
+    /// Size of the synthetic nonce in bytes
+    pub const NONCE_SIZE: usize = NONCE_SIZE;
+
+    /// Size of the Poly1305 tag in bytes
+    pub const TAG_SIZE: usize = TAG_SIZE;
+
+    /// Size of the output in bytes, in addition to the message
+    pub const OVERHEAD: usize = OVERHEAD;
+}
+
+impl<C> SivSecretBox<C>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    /// Encrypt the given message, returning the synthetic nonce followed by
+    /// the tag and ciphertext.
+    #[cfg(feature = "alloc")]
+    pub fn encrypt(&self, context: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::with_capacity(OVERHEAD + plaintext.len());
+        buffer.extend_from_slice(plaintext);
+        self.encrypt_in_place(context, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Decrypt the given output of [`SivSecretBox::encrypt`], returning the
+    /// message.
+    #[cfg(feature = "alloc")]
+    pub fn decrypt(&self, context: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::from(ciphertext);
+        self.decrypt_in_place(context, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Encrypt the message in `buffer` in-place, prepending the synthetic
+    /// nonce and tag.
+    pub fn encrypt_in_place(&self, context: &[u8], buffer: &mut dyn Buffer) -> Result<(), Error> {
+        let pt_len = buffer.len();
+
+        // Make room in the buffer for the nonce and tag. They need to be
+        // prepended, so the message has to be moved.
+        buffer.extend_from_slice(&[0u8; OVERHEAD])?;
+        buffer.as_mut().copy_within(..pt_len, OVERHEAD);
+
+        let (nonce, tag_and_body) = buffer.as_mut().split_at_mut(NONCE_SIZE);
+        let (tag, body) = tag_and_body.split_at_mut(TAG_SIZE);
+        let (computed_nonce, computed_tag) = self.encrypt_in_place_detached(context, body)?;
+        nonce.copy_from_slice(&computed_nonce);
+        tag.copy_from_slice(&computed_tag);
+        Ok(())
+    }
+
+    /// Decrypt the output of [`SivSecretBox::encrypt_in_place`] in `buffer`
+    /// in-place, leaving only the message.
+    pub fn decrypt_in_place(&self, context: &[u8], buffer: &mut dyn Buffer) -> Result<(), Error> {
+        if buffer.len() < OVERHEAD {
+            return Err(Error);
+        }
+
+        let (nonce, tag_and_body) = buffer.as_mut().split_at_mut(NONCE_SIZE);
+        let (tag, body) = tag_and_body.split_at_mut(TAG_SIZE);
+        let nonce = *Nonce::from_slice(nonce);
+        let tag = *Tag::from_slice(tag);
+        self.decrypt_in_place_detached(context, body, &nonce, &tag)?;
+
+        let pt_len = buffer.len() - OVERHEAD;
+        buffer.as_mut().copy_within(OVERHEAD.., 0);
+        buffer.truncate(pt_len);
+        Ok(())
+    }
+
+    /// Encrypt the message in `buffer` in-place, returning the synthetic
+    /// nonce and the tag.
+    pub fn encrypt_in_place_detached(
+        &self,
+        context: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(Nonce, Tag), Error> {
+        let nonce = self.synthetic_nonce(context, buffer)?;
+        let (mut cipher, mac) = self.secretbox.init_cipher_and_mac(&nonce);
+        cipher.apply_keystream(buffer);
+        Ok((nonce, mac.compute_unpadded(buffer)))
+    }
+
+    /// Decrypt the ciphertext in `buffer` in-place, checking it against the
+    /// given synthetic nonce and tag.
+    ///
+    /// If the synthetic nonce doesn't match the decrypted message and
+    /// context, `buffer` is zeroed and [`Error`] is returned.
+    pub fn decrypt_in_place_detached(
+        &self,
+        context: &[u8],
+        buffer: &mut [u8],
+        nonce: &Nonce,
+        tag: &Tag,
+    ) -> Result<(), Error> {
+        let (mut cipher, mac) = self.secretbox.init_cipher_and_mac(nonce);
+        let expected_tag = mac.compute_unpadded(buffer);
+
+        if !bool::from(expected_tag.ct_eq(tag)) {
+            return Err(Error);
+        }
+
+        cipher.apply_keystream(buffer);
+
+        let valid = match self.synthetic_nonce(context, buffer) {
+            Ok(expected_nonce) => expected_nonce.ct_eq(nonce).into(),
+            Err(_) => false,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            // Don't release plaintext which doesn't belong to the context
+            buffer.iter_mut().for_each(|byte| *byte = 0);
+            Err(Error)
+        }
+    }
+
+    /// Compute the synthetic nonce for the given context and message.
+    fn synthetic_nonce(&self, context: &[u8], message: &[u8]) -> Result<Nonce, Error> {
+        let context_len = u64::try_from(context.len()).map_err(|_| Error)?;
+        let message_len = u64::try_from(message.len()).map_err(|_| Error)?;
+
+        let mut lengths = poly1305::Block::default();
+        lengths[..8].copy_from_slice(&context_len.to_le_bytes());
+        lengths[8..].copy_from_slice(&message_len.to_le_bytes());
+
+        let mut mac = Poly1305::new(&self.mac_key);
+        mac.update_padded(context);
+        mac.update_padded(message);
+        mac.update(&[lengths]);
+        let hash = mac.finalize();
+
+        let prf_output = Zeroizing::new(C::kdf(&self.prf_key, &hash));
+        Ok(*Nonce::from_slice(&prf_output[..NONCE_SIZE]))
+    }
+}
+
+// Handwritten instead of derived to avoid `C: Clone` bound
+impl<C> Clone for SivSecretBox<C> {
+    fn clone(&self) -> Self {
+        Self {
+            secretbox: self.secretbox.clone(),
+            mac_key: self.mac_key.clone(),
+            prf_key: self.prf_key.clone(),
+        }
+    }
+}
+
+impl<C> From<SecretKey> for SivSecretBox<C>
+where
+    C: Kdf,
+{
+    fn from(key: SecretKey) -> SivSecretBox<C> {
+        (&key).into()
+    }
+}
+
+impl<C> From<&SecretKey> for SivSecretBox<C>
+where
+    C: Kdf,
+{
+    fn from(key: &SecretKey) -> SivSecretBox<C> {
+        let derive = |label: &[u8; 16]| C::kdf(key.as_ref(), GenericArray::from_slice(label));
+
+        Self {
+            secretbox: SecretKey::from(derive(ENC_KEY_LABEL)).into(),
+            mac_key: Zeroizing::new(derive(MAC_KEY_LABEL)),
+            prf_key: Zeroizing::new(derive(PRF_KEY_LABEL)),
+        }
+    }
+}
+
+impl<C> KeySizeUser for SivSecretBox<C> {
+    type KeySize = U32;
+}
+
+impl<C> KeyInit for SivSecretBox<C>
+where
+    C: Kdf,
+{
+    fn new(key: &Key) -> Self {
+        SecretKey::from(*key).into()
+    }
+}
@@ -0,0 +1,338 @@
+//! Chunked encryption of large messages using the [STREAM] construction.
+//!
+//! [STREAM] splits a message into a sequence of segments which are each
+//! encrypted with [`SecretBox`] under a nonce composed of a fixed prefix, a
+//! segment counter, and a flag marking the last segment. This allows messages
+//! which are too large to fit in memory to be processed incrementally, while
+//! detecting reordered, duplicated or truncated segments.
+//!
+//! This module provides [`SecretBox`]-specific aliases for the STREAM types in
+//! the [`aead::stream`] module. When the `std` feature is enabled,
+//! [`StreamWriter`] and [`StreamReader`] adapt them to [`std::io::Write`] and
+//! [`std::io::Read`].
+//!
+//! # Usage
+//!
+#![cfg_attr(all(feature = "getrandom", feature = "std"), doc = "```")]
+#![cfg_attr(not(all(feature = "getrandom", feature = "std")), doc = "```ignore")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use crypto_secretbox::{
+//!     aead::{rand_core::RngCore, KeyInit, OsRng},
+//!     stream::{StreamReader, StreamWriter},
+//!     XSalsa20Poly1305,
+//! };
+//! use std::io::{Read, Write};
+//!
+//! let key = XSalsa20Poly1305::generate_key(&mut OsRng);
+//! let mut nonce = [0u8; 19];
+//! OsRng.fill_bytes(&mut nonce); // unique per stream
+//! let nonce = nonce.into();
+//!
+//! let mut writer = StreamWriter::new(XSalsa20Poly1305::new(&key), &nonce, 4096, Vec::new());
+//! writer.write_all(b"plaintext message")?;
+//! let ciphertext = writer.finish()?;
+//!
+//! let mut reader = StreamReader::new(XSalsa20Poly1305::new(&key), &nonce, 4096, &ciphertext[..]);
+//! let mut plaintext = Vec::new();
+//! reader.read_to_end(&mut plaintext)?;
+//! assert_eq!(&plaintext, b"plaintext message");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [STREAM]: https://eprint.iacr.org/2015/189.pdf
+
+use crate::SecretBox;
+
+#[cfg(feature = "std")]
+use {
+    crate::{Error, Kdf},
+    aead::consts::{U32, U8},
+    cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher},
+    std::{
+        io::{self, Read, Write},
+        vec::Vec,
+    },
+};
+
+pub use aead::stream::{NewStream, StreamPrimitive};
+
+/// STREAM nonce prefix: the [`SecretBox`] nonce without the 5 bytes used for
+/// the 32-bit big endian counter and last segment flag.
+pub type NoncePrefixBE32<C> = aead::stream::Nonce<SecretBox<C>, StreamBE32<C>>;
+
+/// STREAM nonce prefix: the [`SecretBox`] nonce without the 4 bytes used for
+/// the 31-bit little endian counter and last segment flag.
+pub type NoncePrefixLE31<C> = aead::stream::Nonce<SecretBox<C>, StreamLE31<C>>;
+
+/// STREAM primitive instantiated with [`SecretBox`], using a 32-bit big
+/// endian counter and 1-byte last segment flag.
+pub type StreamBE32<C> = aead::stream::StreamBE32<SecretBox<C>>;
+
+/// STREAM primitive instantiated with [`SecretBox`], using a 31-bit little
+/// endian counter and 1-bit last segment flag.
+pub type StreamLE31<C> = aead::stream::StreamLE31<SecretBox<C>>;
+
+/// STREAM encryptor instantiated with [`StreamBE32`].
+pub type EncryptorBE32<C> = aead::stream::EncryptorBE32<SecretBox<C>>;
+
+/// STREAM decryptor instantiated with [`StreamBE32`].
+pub type DecryptorBE32<C> = aead::stream::DecryptorBE32<SecretBox<C>>;
+
+/// STREAM encryptor instantiated with [`StreamLE31`].
+pub type EncryptorLE31<C> = aead::stream::EncryptorLE31<SecretBox<C>>;
+
+/// STREAM decryptor instantiated with [`StreamLE31`].
+pub type DecryptorLE31<C> = aead::stream::DecryptorLE31<SecretBox<C>>;
+
+/// [`Write`] adapter which encrypts everything written to it as a sequence of
+/// [`EncryptorBE32`] segments.
+///
+/// Every segment except the last one contains exactly `chunk_size` bytes of
+/// plaintext. The last segment contains the remaining (possibly zero) bytes,
+/// so its plaintext is always shorter than `chunk_size`. [`StreamReader`]
+/// relies on this to recognize the last segment.
+///
+/// [`StreamWriter::finish`] must be called to write the last segment.
+/// Dropping the writer without calling it results in a truncated stream,
+/// which will be rejected when decrypting.
+#[cfg(feature = "std")]
+pub struct StreamWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    encryptor: EncryptorBE32<C>,
+    inner: W,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+}
+
+#[cfg(feature = "std")]
+impl<C, W> StreamWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    /// Create a new [`StreamWriter`] which writes segments containing
+    /// `chunk_size` bytes of plaintext to `inner`.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn new(
+        secretbox: SecretBox<C>,
+        nonce: &NoncePrefixBE32<C>,
+        chunk_size: usize,
+        inner: W,
+    ) -> Self {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        Self {
+            encryptor: EncryptorBE32::from_aead(secretbox, nonce),
+            inner,
+            buffer: Vec::with_capacity(chunk_size + SecretBox::<C>::TAG_SIZE),
+            chunk_size,
+        }
+    }
+
+    /// Encrypt and write the last segment, returning the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        let Self {
+            encryptor,
+            mut inner,
+            mut buffer,
+            ..
+        } = self;
+
+        encryptor
+            .encrypt_last_in_place(b"", &mut buffer)
+            .map_err(invalid_data)?;
+
+        inner.write_all(&buffer)?;
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C, W> Write for StreamWriter<C, W>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+
+        if self.buffer.len() == self.chunk_size {
+            self.encryptor
+                .encrypt_next_in_place(b"", &mut self.buffer)
+                .map_err(invalid_data)?;
+
+            self.inner.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // A partial segment can't be written until it's known to be the last
+        self.inner.flush()
+    }
+}
+
+/// [`Read`] adapter which decrypts a sequence of [`DecryptorBE32`] segments
+/// as written by [`StreamWriter`].
+///
+/// Segments are authenticated before any of their plaintext is returned.
+/// Reading fails with [`io::ErrorKind::InvalidData`] if a segment fails to
+/// authenticate, or if the stream is truncated or followed by trailing data.
+#[cfg(feature = "std")]
+pub struct StreamReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read,
+{
+    /// Decryptor, or `None` once the last segment has been decrypted.
+    decryptor: Option<DecryptorBE32<C>>,
+    inner: R,
+    buffer: Vec<u8>,
+    position: usize,
+    chunk_size: usize,
+    failed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<C, R> StreamReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read,
+{
+    /// Create a new [`StreamReader`] which reads segments containing
+    /// `chunk_size` bytes of plaintext from `inner`.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn new(
+        secretbox: SecretBox<C>,
+        nonce: &NoncePrefixBE32<C>,
+        chunk_size: usize,
+        inner: R,
+    ) -> Self {
+        assert!(chunk_size > 0, "chunk size must be non-zero");
+
+        Self {
+            decryptor: Some(DecryptorBE32::from_aead(secretbox, nonce)),
+            inner,
+            buffer: Vec::with_capacity(chunk_size + SecretBox::<C>::TAG_SIZE),
+            position: 0,
+            chunk_size,
+            failed: false,
+        }
+    }
+
+    /// Get the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Read and decrypt the next segment into the buffer.
+    ///
+    /// Returns the decryptor if more segments follow, or `None` if the last
+    /// segment has been decrypted.
+    fn next_segment(
+        &mut self,
+        mut decryptor: DecryptorBE32<C>,
+    ) -> io::Result<Option<DecryptorBE32<C>>> {
+        let segment_size = self.chunk_size + SecretBox::<C>::TAG_SIZE;
+        self.buffer.resize(segment_size, 0);
+        self.position = 0;
+
+        let len = read_full(&mut self.inner, &mut self.buffer)?;
+        self.buffer.truncate(len);
+
+        if len == segment_size {
+            decryptor
+                .decrypt_next_in_place(b"", &mut self.buffer)
+                .map_err(invalid_data)?;
+
+            return Ok(Some(decryptor));
+        }
+
+        decryptor
+            .decrypt_last_in_place(b"", &mut self.buffer)
+            .map_err(invalid_data)?;
+
+        // Reject trailing data after the last segment
+        if read_full(&mut self.inner, &mut [0u8])? != 0 {
+            return Err(invalid_data(Error));
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<C, R> Read for StreamReader<C, R>
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.failed {
+            return Err(invalid_data(Error));
+        }
+
+        while self.position == self.buffer.len() {
+            let decryptor = match self.decryptor.take() {
+                Some(decryptor) => decryptor,
+                None => return Ok(0),
+            };
+
+            match self.next_segment(decryptor) {
+                Ok(decryptor) => self.decryptor = decryptor,
+                Err(e) => {
+                    // Never hand out plaintext from a segment which failed to
+                    // authenticate, and refuse to read any further
+                    self.buffer.clear();
+                    self.position = 0;
+                    self.failed = true;
+                    return Err(e);
+                }
+            }
+        }
+
+        let len = buf.len().min(self.buffer.len() - self.position);
+        buf[..len].copy_from_slice(&self.buffer[self.position..][..len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+/// Fill `buf` from `reader`, stopping early only at end-of-file.
+///
+/// Returns the number of bytes read.
+#[cfg(feature = "std")]
+pub(crate) fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(len)
+}
+
+/// Convert an AEAD [`Error`] into an [`io::Error`].
+#[cfg(feature = "std")]
+pub(crate) fn invalid_data(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -0,0 +1,181 @@
+//! Raw extended-nonce stream ciphers, i.e. NaCl's `crypto_stream`.
+//!
+//! These are the unauthenticated XSalsa20 and XChaCha20 stream ciphers which
+//! [`SecretBox`] is built on: a subkey is derived from the key and the first
+//! 16 bytes of the nonce using the cipher's [`Kdf`] (HSalsa20 or HChaCha20),
+//! and the keystream is generated under the subkey and the last 8 bytes of
+//! the nonce.
+//!
+//! # ⚠️ Security Warning
+//!
+//! Ciphertexts produced by these functions aren't authenticated, and can be
+//! modified by an attacker without detection. Only use them for
+//! interoperability with protocols which require them; use [`SecretBox`]
+//! otherwise.
+//!
+//! [`SecretBox`]: crate::SecretBox
+
+use crate::{Error, Kdf, Key, Nonce};
+use aead::{
+    consts::{U32, U8},
+    generic_array::GenericArray,
+};
+use cipher::{IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher, StreamCipherSeek};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "chacha20")]
+use {crate::crypto_core, chacha20::ChaCha20Legacy as ChaCha20};
+
+#[cfg(feature = "salsa20")]
+use salsa20::Salsa20;
+
+/// Size of a keystream block in bytes, which the initial counter of
+/// [`xor_ic`] is measured in.
+pub const BLOCK_SIZE: usize = 64;
+
+/// Fill `buffer` with the keystream for the given key and nonce.
+///
+/// This is equivalent to libsodium's `crypto_stream_xsalsa20` and
+/// `crypto_stream_xchacha20`.
+pub fn keystream<C>(key: &Key, nonce: &Nonce, buffer: &mut [u8])
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    buffer.iter_mut().for_each(|byte| *byte = 0);
+    xor::<C>(key, nonce, buffer);
+}
+
+/// Encrypt or decrypt `buffer` in-place by XORing it with the keystream for
+/// the given key and nonce.
+///
+/// This is equivalent to libsodium's `crypto_stream_xsalsa20_xor` and
+/// `crypto_stream_xchacha20_xor`.
+pub fn xor<C>(key: &Key, nonce: &Nonce, buffer: &mut [u8])
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8> + StreamCipher,
+{
+    init_cipher::<C>(key, nonce).apply_keystream(buffer);
+}
+
+/// Encrypt or decrypt `buffer` in-place by XORing it with the keystream for
+/// the given key and nonce, starting at block `ic` of the keystream.
+///
+/// This is equivalent to libsodium's `crypto_stream_xsalsa20_xor_ic` and
+/// `crypto_stream_xchacha20_xor_ic`.
+///
+/// Returns [`Error`] if the end of the keystream is reached. The block
+/// counter of `ChaCha20Legacy` is only 32 bits wide, so use
+/// `xchacha20_xor_ic` for libsodium's 64-bit counter.
+pub fn xor_ic<C>(key: &Key, nonce: &Nonce, ic: u64, buffer: &mut [u8]) -> Result<(), Error>
+where
+    C: Kdf
+        + KeyIvInit
+        + KeySizeUser<KeySize = U32>
+        + IvSizeUser<IvSize = U8>
+        + StreamCipher
+        + StreamCipherSeek,
+{
+    let mut cipher = init_cipher::<C>(key, nonce);
+    cipher
+        .try_seek(u128::from(ic) * BLOCK_SIZE as u128)
+        .map_err(|_| Error)?;
+    cipher.try_apply_keystream(buffer).map_err(|_| Error)
+}
+
+/// Fill `buffer` with the XSalsa20 keystream for the given key and nonce.
+#[cfg(feature = "salsa20")]
+pub fn xsalsa20(key: &Key, nonce: &Nonce, buffer: &mut [u8]) {
+    keystream::<Salsa20>(key, nonce, buffer)
+}
+
+/// XOR `buffer` with the XSalsa20 keystream for the given key and nonce.
+#[cfg(feature = "salsa20")]
+pub fn xsalsa20_xor(key: &Key, nonce: &Nonce, buffer: &mut [u8]) {
+    xor::<Salsa20>(key, nonce, buffer)
+}
+
+/// XOR `buffer` with the XSalsa20 keystream for the given key and nonce,
+/// starting at block `ic`.
+#[cfg(feature = "salsa20")]
+pub fn xsalsa20_xor_ic(key: &Key, nonce: &Nonce, ic: u64, buffer: &mut [u8]) -> Result<(), Error> {
+    xor_ic::<Salsa20>(key, nonce, ic, buffer)
+}
+
+/// Fill `buffer` with the XChaCha20 keystream for the given key and nonce.
+#[cfg(feature = "chacha20")]
+pub fn xchacha20(key: &Key, nonce: &Nonce, buffer: &mut [u8]) {
+    keystream::<ChaCha20>(key, nonce, buffer)
+}
+
+/// XOR `buffer` with the XChaCha20 keystream for the given key and nonce.
+#[cfg(feature = "chacha20")]
+pub fn xchacha20_xor(key: &Key, nonce: &Nonce, buffer: &mut [u8]) {
+    xor::<ChaCha20>(key, nonce, buffer)
+}
+
+/// XOR `buffer` with the XChaCha20 keystream for the given key and nonce,
+/// starting at block `ic`.
+///
+/// Like libsodium, the block counter is 64 bits wide, even though
+/// [`ChaCha20Legacy`] only supports 32 bits. Returns [`Error`] if the counter
+/// would overflow.
+///
+/// [`ChaCha20Legacy`]: chacha20::ChaCha20Legacy
+#[cfg(feature = "chacha20")]
+pub fn xchacha20_xor_ic(key: &Key, nonce: &Nonce, ic: u64, buffer: &mut [u8]) -> Result<(), Error> {
+    let (nonce_prefix, nonce_suffix) = nonce.split_at(16);
+    let subkey = Zeroizing::new(ChaCha20::kdf(key, GenericArray::from_slice(nonce_prefix)));
+    let nonce_suffix: &[u8; 8] = nonce_suffix.try_into().unwrap();
+
+    let mut counter = ic;
+    let mut buffer = buffer;
+
+    loop {
+        let low = counter as u32;
+
+        let len = if low == u32::MAX {
+            // The `chacha20` crate can't produce the last block before the
+            // low word of the counter wraps around
+            let block = crypto_core::chacha20_block(&subkey, counter, nonce_suffix);
+            let len = buffer.len().min(BLOCK_SIZE);
+            for (byte, key) in buffer[..len].iter_mut().zip(block.iter()) {
+                *byte ^= key;
+            }
+            len
+        } else {
+            // The original ChaCha20 is the IETF variant with the high word of
+            // the counter as the first word of the nonce
+            let mut iv = chacha20::Nonce::default();
+            iv[..4].copy_from_slice(&((counter >> 32) as u32).to_le_bytes());
+            iv[4..].copy_from_slice(nonce_suffix);
+
+            let mut cipher = chacha20::ChaCha20::new(&subkey, &iv);
+            cipher.seek(u64::from(low) * BLOCK_SIZE as u64);
+
+            let available = u64::from(u32::MAX - low) * BLOCK_SIZE as u64;
+            let len = usize::try_from(available).map_or(buffer.len(), |n| buffer.len().min(n));
+            cipher.apply_keystream(&mut buffer[..len]);
+            len
+        };
+
+        buffer = &mut core::mem::take(&mut buffer)[len..];
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        // A whole number of blocks has been used if any data is left
+        counter = counter
+            .checked_add((len / BLOCK_SIZE) as u64)
+            .ok_or(Error)?;
+    }
+}
+
+/// Initialize the cipher under the subkey derived for the given nonce.
+fn init_cipher<C>(key: &Key, nonce: &Nonce) -> C
+where
+    C: Kdf + KeyIvInit + KeySizeUser<KeySize = U32> + IvSizeUser<IvSize = U8>,
+{
+    let (nonce_prefix, nonce_suffix) = nonce.split_at(16);
+    let subkey = Zeroizing::new(C::kdf(key, GenericArray::from_slice(nonce_prefix)));
+    C::new(&subkey, GenericArray::from_slice(nonce_suffix))
+}
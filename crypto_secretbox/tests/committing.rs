@@ -0,0 +1,135 @@
+//! Key-committing SecretBox tests.
+//!
+//! The tag and ciphertext following the commitment are checked against the
+//! output of the corresponding `SecretBox`; the commitments are regression
+//! vectors.
+
+#![cfg(any(feature = "chacha20", feature = "salsa20"))]
+
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const OTHER_KEY: &[u8; 32] =
+    &hex!("0000000000000000000000000000000000000000000000000000000000000001");
+const NONCE: &[u8; 24] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+const PLAINTEXT: &[u8] = b"committing secretbox test message";
+
+macro_rules! impl_tests {
+    ($committing:path, $secretbox:path, $commitment:expr) => {
+        #[test]
+        fn round_trip() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$committing>::new(key);
+            let ciphertext = cipher.encrypt(nonce, PLAINTEXT).unwrap();
+            assert_eq!(ciphertext.len(), PLAINTEXT.len() + 48);
+
+            let plaintext = cipher.decrypt(nonce, ciphertext.as_slice()).unwrap();
+            assert_eq!(PLAINTEXT, plaintext.as_slice());
+        }
+
+        #[test]
+        fn layout() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let ciphertext = <$committing>::new(key).encrypt(nonce, PLAINTEXT).unwrap();
+            let secretbox_ciphertext = <$secretbox>::new(key).encrypt(nonce, PLAINTEXT).unwrap();
+
+            let (commitment, rest) = ciphertext.split_at(32);
+            assert_eq!($commitment, commitment);
+            assert_eq!(secretbox_ciphertext, rest);
+        }
+
+        #[test]
+        fn decrypt_wrong_key() {
+            let nonce = GenericArray::from_slice(NONCE);
+            let ciphertext = <$committing>::new(GenericArray::from_slice(KEY))
+                .encrypt(nonce, PLAINTEXT)
+                .unwrap();
+
+            let cipher = <$committing>::new(GenericArray::from_slice(OTHER_KEY));
+            assert!(cipher.decrypt(nonce, ciphertext.as_slice()).is_err());
+        }
+
+        #[test]
+        fn decrypt_other_commitment() {
+            // A ciphertext which passes Poly1305 under one key is still
+            // rejected when the commitment belongs to another key
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut ciphertext = <$committing>::new(GenericArray::from_slice(KEY))
+                .encrypt(nonce, PLAINTEXT)
+                .unwrap();
+            let other = <$committing>::new(GenericArray::from_slice(OTHER_KEY))
+                .encrypt(nonce, PLAINTEXT)
+                .unwrap();
+            ciphertext[..32].copy_from_slice(&other[..32]);
+
+            for key in [KEY, OTHER_KEY] {
+                let cipher = <$committing>::new(GenericArray::from_slice(key));
+                assert!(cipher.decrypt(nonce, ciphertext.as_slice()).is_err());
+            }
+        }
+
+        #[test]
+        fn decrypt_modified_commitment() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$committing>::new(key);
+            let mut ciphertext = cipher.encrypt(nonce, PLAINTEXT).unwrap();
+
+            // Tweak the first byte of the commitment
+            ciphertext[0] ^= 0xaa;
+            assert!(cipher.decrypt(nonce, ciphertext.as_slice()).is_err());
+        }
+
+        #[test]
+        fn encrypt_in_place_rejects_aad() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let mut buffer = PLAINTEXT.to_vec();
+
+            assert!(<$committing>::new(key)
+                .encrypt_in_place(nonce, b"aad", &mut buffer)
+                .is_err());
+            assert_eq!(buffer, PLAINTEXT);
+        }
+
+        #[test]
+        fn decrypt_truncated() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$committing>::new(key);
+            assert!(cipher.decrypt(nonce, [0u8; 47].as_slice()).is_err());
+        }
+    };
+}
+
+#[cfg(feature = "chacha20")]
+mod xchacha20poly1305 {
+    use super::{KEY, NONCE, OTHER_KEY, PLAINTEXT};
+    use crypto_secretbox::{
+        aead::{generic_array::GenericArray, Aead, AeadInPlace, KeyInit},
+        CommittingXChaCha20Poly1305, XChaCha20Poly1305,
+    };
+    use hex_literal::hex;
+
+    const COMMITMENT: &[u8] =
+        &hex!("d20df2850fff94329add502953139d6b0d3cb2ea35d4b2a13ac865cfc6fbc68d");
+
+    impl_tests!(CommittingXChaCha20Poly1305, XChaCha20Poly1305, COMMITMENT);
+}
+
+#[cfg(feature = "salsa20")]
+mod xsalsa20poly1305 {
+    use super::{KEY, NONCE, OTHER_KEY, PLAINTEXT};
+    use crypto_secretbox::{
+        aead::{generic_array::GenericArray, Aead, AeadInPlace, KeyInit},
+        CommittingXSalsa20Poly1305, XSalsa20Poly1305,
+    };
+    use hex_literal::hex;
+
+    const COMMITMENT: &[u8] =
+        &hex!("f409e1cc48c57a6ffb9de2e52875a48ddfa022fcaa6f6a933da5e2466238ad84");
+
+    impl_tests!(CommittingXSalsa20Poly1305, XSalsa20Poly1305, COMMITMENT);
+}
@@ -0,0 +1,117 @@
+//! Container format tests.
+
+#![cfg(all(feature = "alloc", feature = "getrandom", feature = "salsa20"))]
+
+use crypto_secretbox::{
+    aead::{Aead, KeyInit, OsRng},
+    container::{Algorithm, Container, HEADER_SIZE, VERSION},
+    crypto_core, XSalsa20Poly1305,
+};
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const PLAINTEXT: &[u8] = b"stored record";
+
+fn cipher() -> XSalsa20Poly1305 {
+    XSalsa20Poly1305::new(KEY.into())
+}
+
+#[test]
+fn algorithm_ids() {
+    for algorithm in [Algorithm::XSalsa20Poly1305, Algorithm::XChaCha20Poly1305] {
+        assert_eq!(Algorithm::try_from(algorithm.id()), Ok(algorithm));
+    }
+
+    assert_eq!(Algorithm::XSalsa20Poly1305.id(), 1);
+    assert_eq!(Algorithm::XChaCha20Poly1305.id(), 2);
+    assert!(Algorithm::try_from(0).is_err());
+    assert!(Algorithm::try_from(3).is_err());
+}
+
+#[test]
+fn layout() {
+    let container = cipher()
+        .encrypt_to_container(&mut OsRng, PLAINTEXT)
+        .unwrap();
+    assert_eq!(container[0], VERSION);
+    assert_eq!(container[1], Algorithm::XSalsa20Poly1305.id());
+    assert_eq!(container.len(), HEADER_SIZE + 16 + PLAINTEXT.len());
+
+    let parsed = Container::parse(&container).unwrap();
+    assert_eq!(parsed.algorithm(), Algorithm::XSalsa20Poly1305);
+    assert_eq!(parsed.nonce().as_slice(), &container[2..HEADER_SIZE]);
+
+    // Encrypted under a subkey bound to the version and algorithm
+    let mut input = [0u8; 16];
+    input[..14].copy_from_slice(b"crypto_sbcntnr");
+    input[14] = VERSION;
+    input[15] = Algorithm::XSalsa20Poly1305.id();
+    let subkey = crypto_core::hsalsa20(KEY.into(), &input, None);
+    assert_eq!(
+        parsed.ciphertext(),
+        XSalsa20Poly1305::new(&subkey)
+            .encrypt(parsed.nonce(), PLAINTEXT)
+            .unwrap()
+    );
+    assert_ne!(
+        parsed.ciphertext(),
+        cipher().encrypt(parsed.nonce(), PLAINTEXT).unwrap()
+    );
+}
+
+#[test]
+fn round_trip() {
+    let container = cipher()
+        .encrypt_to_container(&mut OsRng, PLAINTEXT)
+        .unwrap();
+    assert_eq!(cipher().open_container(&container).unwrap(), PLAINTEXT);
+
+    let wrong_key = XSalsa20Poly1305::new(&[0u8; 32].into());
+    assert!(wrong_key.open_container(&container).is_err());
+}
+
+#[cfg(feature = "chacha20")]
+#[test]
+fn open_other_algorithm() {
+    use crypto_secretbox::XChaCha20Poly1305;
+
+    let xchacha = XChaCha20Poly1305::new(KEY.into());
+    let container = xchacha.encrypt_to_container(&mut OsRng, PLAINTEXT).unwrap();
+    assert_eq!(container[1], Algorithm::XChaCha20Poly1305.id());
+
+    // Containers are opened with the algorithm they were produced with
+    assert_eq!(cipher().open_container(&container).unwrap(), PLAINTEXT);
+    let container = cipher()
+        .encrypt_to_container(&mut OsRng, PLAINTEXT)
+        .unwrap();
+    assert_eq!(xchacha.open_container(&container).unwrap(), PLAINTEXT);
+
+    // Relabeling a container with another algorithm changes the subkey
+    let mut relabeled = container;
+    relabeled[1] = Algorithm::XChaCha20Poly1305.id();
+    assert!(cipher().open_container(&relabeled).is_err());
+}
+
+#[test]
+fn invalid() {
+    let container = cipher()
+        .encrypt_to_container(&mut OsRng, PLAINTEXT)
+        .unwrap();
+
+    let mut bad_version = container.clone();
+    bad_version[0] = VERSION + 1;
+    assert!(cipher().open_container(&bad_version).is_err());
+
+    let mut bad_algorithm = container.clone();
+    bad_algorithm[1] = 0xff;
+    assert!(cipher().open_container(&bad_algorithm).is_err());
+
+    let mut bad_nonce = container.clone();
+    bad_nonce[2] ^= 1;
+    assert!(cipher().open_container(&bad_nonce).is_err());
+
+    assert!(cipher()
+        .open_container(&container[..HEADER_SIZE + 15])
+        .is_err());
+    assert!(cipher().open_container(&[]).is_err());
+}
@@ -0,0 +1,90 @@
+//! `crypto_core` tests.
+//!
+//! Test vectors generated using `test-vector-gen` which uses a libsodium
+//! reference, except for the HSalsa20 vector adapted from NaCl's
+//! `tests/core1.c` and `tests/core1.out`.
+
+#![cfg(any(feature = "chacha20", feature = "salsa20"))]
+
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const INPUT: &[u8; 16] = &hex!("69696ee955b62b73cd62bda875fc73d6");
+const CONSTANT: &[u8; 16] = b"custom constant!";
+
+/// `"expand 32-byte k"`, which is used when no constant is given.
+const SIGMA: &[u8; 16] = b"expand 32-byte k";
+
+#[cfg(feature = "salsa20")]
+mod hsalsa20 {
+    use super::{CONSTANT, INPUT, KEY, SIGMA};
+    use crypto_secretbox::crypto_core::hsalsa20;
+    use hex_literal::hex;
+
+    const DEFAULT_CONSTANT_OUTPUT: &[u8; 32] =
+        &hex!("dc908dda0b9344a953629b733820778880f3ceb421bb61b91cbd4c3e66256ce4");
+    const CUSTOM_CONSTANT_OUTPUT: &[u8; 32] =
+        &hex!("aede9c63f1d7f79a48fb5d9d57757929f880ea2d4f201204ab9b6470712a05e7");
+
+    #[test]
+    fn default_constant() {
+        assert_eq!(
+            hsalsa20(KEY.into(), INPUT, None).as_slice(),
+            DEFAULT_CONSTANT_OUTPUT
+        );
+
+        // An explicit standard constant takes the generic path
+        assert_eq!(
+            hsalsa20(KEY.into(), INPUT, Some(SIGMA)).as_slice(),
+            DEFAULT_CONSTANT_OUTPUT
+        );
+    }
+
+    #[test]
+    fn custom_constant() {
+        assert_eq!(
+            hsalsa20(KEY.into(), INPUT, Some(CONSTANT)).as_slice(),
+            CUSTOM_CONSTANT_OUTPUT
+        );
+    }
+
+    #[test]
+    fn nacl_core1() {
+        let shared = hex!("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(hsalsa20(&shared.into(), &[0u8; 16], None).as_slice(), KEY);
+    }
+}
+
+#[cfg(feature = "chacha20")]
+mod hchacha20 {
+    use super::{CONSTANT, INPUT, KEY, SIGMA};
+    use crypto_secretbox::crypto_core::hchacha20;
+    use hex_literal::hex;
+
+    const DEFAULT_CONSTANT_OUTPUT: &[u8; 32] =
+        &hex!("288be1c77c2ff68b94457d50a3135fd9639d709dd97d1d1cd76c17844cbab06d");
+    const CUSTOM_CONSTANT_OUTPUT: &[u8; 32] =
+        &hex!("c5b718369094981993f53adf47defc499c0d1da8c0cee8bc878a1494d4118d96");
+
+    #[test]
+    fn default_constant() {
+        assert_eq!(
+            hchacha20(KEY.into(), INPUT, None).as_slice(),
+            DEFAULT_CONSTANT_OUTPUT
+        );
+
+        // An explicit standard constant takes the generic path
+        assert_eq!(
+            hchacha20(KEY.into(), INPUT, Some(SIGMA)).as_slice(),
+            DEFAULT_CONSTANT_OUTPUT
+        );
+    }
+
+    #[test]
+    fn custom_constant() {
+        assert_eq!(
+            hchacha20(KEY.into(), INPUT, Some(CONSTANT)).as_slice(),
+            CUSTOM_CONSTANT_OUTPUT
+        );
+    }
+}
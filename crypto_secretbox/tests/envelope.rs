@@ -0,0 +1,141 @@
+//! Envelope encryption tests.
+
+#![cfg(all(feature = "alloc", feature = "getrandom", feature = "salsa20"))]
+
+use crypto_secretbox::{
+    aead::{KeyInit, OsRng},
+    envelope::{Envelope, MAX_KEY_ID_SIZE, VERSION, WRAPPED_KEY_SIZE},
+    XSalsa20Poly1305,
+};
+use hex_literal::hex;
+
+const KEK: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const NEW_KEK: &[u8; 32] =
+    &hex!("8a9b4fd1b3c20c10e5c2e3f34b7cdc0fb1d4a0a9f6ba7f1c5e2a11d0df3a2c45");
+const PLAINTEXT: &[u8] = b"payload encrypted under a per-object data key";
+
+fn kek() -> XSalsa20Poly1305 {
+    XSalsa20Poly1305::new(KEK.into())
+}
+
+fn new_kek() -> XSalsa20Poly1305 {
+    XSalsa20Poly1305::new(NEW_KEK.into())
+}
+
+#[test]
+fn seal_and_open() {
+    let envelope = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT).unwrap();
+    assert_eq!(envelope.key_id(), b"kek-1");
+    assert_eq!(envelope.open(&kek()).unwrap(), PLAINTEXT);
+    assert!(envelope.open(&new_kek()).is_err());
+
+    // Each envelope gets its own data key
+    let other = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT).unwrap();
+    assert_ne!(envelope.to_vec()[79..], other.to_vec()[79..]);
+}
+
+#[test]
+fn serialization() {
+    let envelope = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT).unwrap();
+    let bytes = envelope.to_vec();
+
+    assert_eq!(bytes[0], VERSION);
+    assert_eq!(bytes[1], 5);
+    assert_eq!(&bytes[2..7], b"kek-1");
+    assert_eq!(
+        bytes.len(),
+        7 + 24 + WRAPPED_KEY_SIZE + 24 + 16 + PLAINTEXT.len()
+    );
+
+    let parsed = Envelope::from_slice(&bytes).unwrap();
+    assert_eq!(parsed, envelope);
+    assert_eq!(parsed.open(&kek()).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn invalid_serialization() {
+    let bytes = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT)
+        .unwrap()
+        .to_vec();
+
+    let mut bad_version = bytes.clone();
+    bad_version[0] = VERSION + 1;
+    assert!(Envelope::from_slice(&bad_version).is_err());
+
+    let mut bad_key_id_len = bytes.clone();
+    bad_key_id_len[1] = 0xff;
+    assert!(Envelope::from_slice(&bad_key_id_len).is_err());
+
+    let truncated = bytes.len() - PLAINTEXT.len() - 1;
+    for len in [0, 1, 6, 30, truncated] {
+        assert!(Envelope::from_slice(&bytes[..len]).is_err());
+    }
+
+    let mut tampered = bytes;
+    *tampered.last_mut().unwrap() ^= 1;
+    let envelope = Envelope::from_slice(&tampered).unwrap();
+    assert!(envelope.open(&kek()).is_err());
+}
+
+#[test]
+fn relabeled() {
+    let bytes = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT)
+        .unwrap()
+        .to_vec();
+
+    let mut relabeled = bytes.clone();
+    relabeled[2..7].copy_from_slice(b"kek-2");
+    let envelope = Envelope::from_slice(&relabeled).unwrap();
+    assert_eq!(envelope.key_id(), b"kek-2");
+    assert!(envelope.open(&kek()).is_err());
+
+    // Key IDs of a different length
+    for key_id in [&b""[..], b"kek-10"] {
+        let mut relabeled = vec![VERSION, key_id.len() as u8];
+        relabeled.extend_from_slice(key_id);
+        relabeled.extend_from_slice(&bytes[7..]);
+        let envelope = Envelope::from_slice(&relabeled).unwrap();
+        assert!(envelope.open(&kek()).is_err());
+    }
+}
+
+#[test]
+fn rewrap() {
+    let mut envelope = Envelope::seal(&kek(), b"kek-1", &mut OsRng, PLAINTEXT).unwrap();
+    let before = envelope.to_vec();
+
+    // Unwrapping with the wrong KEK leaves the envelope untouched
+    assert!(envelope
+        .rewrap(&new_kek(), &new_kek(), b"kek-2", &mut OsRng)
+        .is_err());
+    assert_eq!(envelope.to_vec(), before);
+
+    envelope
+        .rewrap(&kek(), &new_kek(), b"kek-2", &mut OsRng)
+        .unwrap();
+    let after = envelope.to_vec();
+
+    assert_eq!(envelope.key_id(), b"kek-2");
+    assert_eq!(envelope.open(&new_kek()).unwrap(), PLAINTEXT);
+    assert!(envelope.open(&kek()).is_err());
+
+    // The payload nonce and ciphertext are unchanged
+    let payload_len = 24 + 16 + PLAINTEXT.len();
+    assert_eq!(
+        before[before.len() - payload_len..],
+        after[after.len() - payload_len..]
+    );
+}
+
+#[test]
+fn key_id_too_long() {
+    let key_id = [0u8; MAX_KEY_ID_SIZE + 1];
+    assert!(Envelope::seal(&kek(), &key_id, &mut OsRng, PLAINTEXT).is_err());
+
+    let mut envelope =
+        Envelope::seal(&kek(), &key_id[..MAX_KEY_ID_SIZE], &mut OsRng, PLAINTEXT).unwrap();
+    assert_eq!(Envelope::from_slice(&envelope.to_vec()).unwrap(), envelope);
+    assert!(envelope
+        .rewrap(&kek(), &new_kek(), &key_id, &mut OsRng)
+        .is_err());
+}
@@ -0,0 +1,146 @@
+//! IETF ChaCha20Poly1305 and XChaCha20Poly1305 tests.
+//!
+//! libsodium test vectors generated with `crypto_aead_chacha20poly1305_ietf`
+//! and `crypto_aead_xchacha20poly1305_ietf` using `test-vector-gen`.
+
+#![cfg(all(feature = "alloc", feature = "chacha20"))]
+
+use crypto_secretbox::{
+    aead::{generic_array::GenericArray, Aead, AeadInPlace, KeyInit, Payload},
+    ChaCha20Poly1305Ietf, XChaCha20Poly1305Ietf,
+};
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f");
+const NONCE: &[u8; 24] = &hex!("404142434445464748494a4b4c4d4e4f5051525354555657");
+const AAD: &[u8] = &hex!("50515253c0c1c2c3c4c5c6c7");
+const PLAINTEXT: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+macro_rules! impl_tests {
+    ($cipher:ty, $nonce:expr, $ciphertext:expr) => {
+        #[test]
+        fn encrypt() {
+            let cipher = <$cipher>::new(KEY.into());
+            let payload = Payload {
+                msg: PLAINTEXT,
+                aad: AAD,
+            };
+            let ciphertext = cipher
+                .encrypt(GenericArray::from_slice($nonce), payload)
+                .unwrap();
+            assert_eq!($ciphertext, ciphertext.as_slice());
+        }
+
+        #[test]
+        fn encrypt_in_place_detached() {
+            let cipher = <$cipher>::new(KEY.into());
+            let mut buffer = PLAINTEXT.to_vec();
+            let tag = cipher
+                .encrypt_in_place_detached(GenericArray::from_slice($nonce), AAD, &mut buffer)
+                .unwrap();
+
+            let (ciphertext, expected_tag) = $ciphertext.split_at(PLAINTEXT.len());
+            assert_eq!(ciphertext, buffer.as_slice());
+            assert_eq!(expected_tag, tag.as_slice());
+        }
+
+        #[test]
+        fn decrypt() {
+            let cipher = <$cipher>::new(KEY.into());
+            let payload = Payload {
+                msg: $ciphertext,
+                aad: AAD,
+            };
+            let plaintext = cipher
+                .decrypt(GenericArray::from_slice($nonce), payload)
+                .unwrap();
+            assert_eq!(PLAINTEXT, plaintext.as_slice());
+        }
+
+        #[test]
+        fn decrypt_modified() {
+            let cipher = <$cipher>::new(KEY.into());
+            let nonce = GenericArray::from_slice($nonce);
+
+            let mut ciphertext = $ciphertext.to_vec();
+            ciphertext[0] ^= 0xaa;
+            let payload = Payload {
+                msg: &ciphertext,
+                aad: AAD,
+            };
+            assert!(cipher.decrypt(nonce, payload).is_err());
+
+            let mut aad = AAD.to_vec();
+            aad[0] ^= 0xaa;
+            let payload = Payload {
+                msg: $ciphertext,
+                aad: &aad,
+            };
+            assert!(cipher.decrypt(nonce, payload).is_err());
+
+            // Dropping the associated data also fails
+            assert!(cipher.decrypt(nonce, $ciphertext).is_err());
+        }
+
+        #[test]
+        fn round_trip_empty() {
+            let cipher = <$cipher>::new(KEY.into());
+            let nonce = GenericArray::from_slice($nonce);
+
+            let ciphertext = cipher.encrypt(nonce, &b""[..]).unwrap();
+            assert_eq!(ciphertext.len(), 16);
+            assert_eq!(cipher.decrypt(nonce, ciphertext.as_slice()).unwrap(), b"");
+        }
+    };
+}
+
+mod chacha20poly1305_ietf {
+    use super::*;
+
+    const CIPHERTEXT: &[u8] = &hex!(
+        "11e13653fb6a1b9447cb3b36a1b773097275eb2cfebba4aaafcf70d848e0e9b3"
+        "4b3edd5c466d239d6d1b83bda25b129320a1475177282891752bc9748a747bdf"
+        "02176832b39bbafc01cd1f4f82bf7701723973eb1e7689b1a935bbdfd2b5460b"
+        "4afcfcd9ded826ceab208f5134592ea2cc3d84114ad9a236d5fdaf9aa8f713ee"
+        "3993"
+    );
+
+    impl_tests!(ChaCha20Poly1305Ietf, &NONCE[..12], CIPHERTEXT);
+
+    /// Test vector from RFC 8439 section 2.8.2.
+    #[test]
+    fn rfc8439() {
+        let cipher = ChaCha20Poly1305Ietf::new(KEY.into());
+        let nonce = hex!("070000004041424344454647");
+        let payload = Payload {
+            msg: PLAINTEXT,
+            aad: AAD,
+        };
+        let ciphertext = cipher.encrypt(&nonce.into(), payload).unwrap();
+        assert_eq!(
+            ciphertext,
+            hex!(
+                "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6"
+                "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36"
+                "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc"
+                "3ff4def08e4b7a9de576d26586cec64b6116"
+                "1ae10b594f09e26a7e902ecbd0600691"
+            )
+        );
+    }
+}
+
+mod xchacha20poly1305_ietf {
+    use super::*;
+
+    /// Also matches `draft-irtf-cfrg-xchacha` appendix A.3.1.
+    const CIPHERTEXT: &[u8] = &hex!(
+        "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb"
+        "731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452"
+        "2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9"
+        "21f9664c97637da9768812f615c68b13b52ec0875924c1c7987947deafd8780a"
+        "cf49"
+    );
+
+    impl_tests!(XChaCha20Poly1305Ietf, NONCE, CIPHERTEXT);
+}
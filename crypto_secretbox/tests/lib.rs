@@ -31,6 +31,20 @@ macro_rules! impl_tests {
             assert_eq!($ciphertext, ciphertext.as_slice());
         }
 
+        #[test]
+        fn encrypt_in_place_prefixed() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let mut buffer = vec![0u8; 16];
+            buffer.extend_from_slice(PLAINTEXT);
+            cipher
+                .encrypt_in_place_prefixed(nonce, &mut buffer)
+                .unwrap();
+
+            assert_eq!($ciphertext, buffer.as_slice());
+        }
+
         #[test]
         fn decrypt() {
             let key = GenericArray::from_slice(KEY);
@@ -41,6 +55,254 @@ macro_rules! impl_tests {
             assert_eq!(PLAINTEXT, plaintext.as_slice());
         }
 
+        #[test]
+        fn decrypt_in_place_prefixed() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let mut buffer = Vec::from($ciphertext);
+            let plaintext = cipher
+                .decrypt_in_place_prefixed(nonce, &mut buffer)
+                .unwrap();
+
+            assert_eq!(PLAINTEXT, plaintext);
+        }
+
+        #[test]
+        fn encrypt_incremental() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+
+            for chunk_size in [1, 7, 16, 17, 64] {
+                let mut buffer = PLAINTEXT.to_vec();
+                let mut encryptor = cipher.encryptor(nonce);
+
+                for chunk in buffer.chunks_mut(chunk_size) {
+                    encryptor.update(chunk);
+                }
+
+                let tag = encryptor.finalize();
+                assert_eq!(&$ciphertext[..16], tag.as_slice());
+                assert_eq!(&$ciphertext[16..], buffer.as_slice());
+            }
+        }
+
+        #[test]
+        fn decrypt_incremental() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let tag = GenericArray::from_slice(&$ciphertext[..16]);
+
+            for chunk_size in [1, 7, 16, 17, 64] {
+                let mut buffer = $ciphertext[16..].to_vec();
+                let mut verifier = cipher.verifier(nonce);
+
+                for chunk in buffer.chunks(chunk_size) {
+                    verifier.update(chunk);
+                }
+
+                let mut decryptor = verifier.verify(tag).unwrap();
+
+                for chunk in buffer.chunks_mut(chunk_size) {
+                    decryptor.update(chunk);
+                }
+
+                assert_eq!(PLAINTEXT, buffer.as_slice());
+            }
+        }
+
+        #[test]
+        fn decrypt_incremental_modified() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let tag = GenericArray::from_slice(&$ciphertext[..16]);
+            let mut ciphertext = $ciphertext[16..].to_vec();
+
+            // Tweak the last byte
+            *ciphertext.last_mut().unwrap() ^= 0xaa;
+
+            let mut verifier = cipher.verifier(nonce);
+            verifier.update(&ciphertext);
+            assert!(verifier.verify(tag).is_err());
+        }
+
+        #[test]
+        fn encrypt_vectored() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let mut buffer = PLAINTEXT.to_vec();
+
+            {
+                let (a, rest) = buffer.split_at_mut(5);
+                let (b, rest) = rest.split_at_mut(0);
+                let (c, d) = rest.split_at_mut(40);
+                let mut segments = [a, b, c, d];
+                let tag = cipher.encrypt_vectored_detached(nonce, &mut segments);
+                assert_eq!(&$ciphertext[..16], tag.as_slice());
+            }
+
+            assert_eq!(&$ciphertext[16..], buffer.as_slice());
+        }
+
+        #[test]
+        fn decrypt_vectored() {
+            use std::io::IoSliceMut;
+
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let tag = GenericArray::from_slice(&$ciphertext[..16]);
+            let mut buffer = $ciphertext[16..].to_vec();
+
+            {
+                let (a, b) = buffer.split_at_mut(17);
+                let mut segments = [IoSliceMut::new(a), IoSliceMut::new(b)];
+                cipher
+                    .decrypt_vectored_detached(nonce, &mut segments, tag)
+                    .unwrap();
+            }
+
+            assert_eq!(PLAINTEXT, buffer.as_slice());
+        }
+
+        #[test]
+        fn decrypt_vectored_modified() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let tag = GenericArray::from_slice(&$ciphertext[..16]);
+            let mut buffer = $ciphertext[16..].to_vec();
+
+            // Tweak the last byte
+            *buffer.last_mut().unwrap() ^= 0xaa;
+            let expected = buffer.clone();
+
+            {
+                let (a, b) = buffer.split_at_mut(17);
+                let mut segments = [a, b];
+                assert!(cipher
+                    .decrypt_vectored_detached(nonce, &mut segments, tag)
+                    .is_err());
+            }
+
+            assert_eq!(expected, buffer);
+        }
+
+        #[test]
+        fn verify() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            assert!(cipher.verify(nonce, $ciphertext).is_ok());
+
+            let (tag, ciphertext) = $ciphertext.split_at(16);
+            assert!(cipher
+                .verify_detached(nonce, ciphertext, GenericArray::from_slice(tag))
+                .is_ok());
+
+            let mut modified = $ciphertext.to_vec();
+            modified[20] ^= 0x01;
+            assert!(cipher.verify(nonce, &modified).is_err());
+
+            let mut modified_tag = $ciphertext.to_vec();
+            modified_tag[0] ^= 0x01;
+            assert!(cipher.verify(nonce, &modified_tag).is_err());
+
+            assert!(cipher.verify(nonce, &$ciphertext[..15]).is_err());
+        }
+
+        #[test]
+        fn encrypt_batch() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+
+            let mut buffers = vec![vec![0u8; 16]; 3];
+            for buffer in &mut buffers {
+                buffer.extend_from_slice(PLAINTEXT);
+            }
+            buffers[2].truncate(15);
+
+            let mut items: Vec<_> = buffers
+                .iter_mut()
+                .map(|buffer| (*nonce, buffer.as_mut_slice()))
+                .collect();
+            let results = cipher.encrypt_batch(&mut items);
+
+            assert_eq!(results, [Ok(()), Ok(()), Err(Error)]);
+            assert_eq!($ciphertext, buffers[0].as_slice());
+            assert_eq!($ciphertext, buffers[1].as_slice());
+        }
+
+        #[test]
+        fn decrypt_batch() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+
+            let mut buffers = vec![$ciphertext.to_vec(); 3];
+            buffers[1][0] ^= 0xaa;
+
+            let mut items: Vec<_> = buffers
+                .iter_mut()
+                .map(|buffer| (*nonce, buffer.as_mut_slice()))
+                .collect();
+            let results = cipher.decrypt_batch(&mut items);
+
+            assert_eq!(results, [Ok(()), Err(Error), Ok(())]);
+            assert_eq!(PLAINTEXT, &buffers[0][16..]);
+            assert_eq!(PLAINTEXT, &buffers[2][16..]);
+        }
+
+        #[test]
+        fn legacy_encrypt_in_place() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let mut buffer = vec![0u8; legacy::ZEROBYTES];
+            buffer.extend_from_slice(PLAINTEXT);
+            legacy::encrypt_in_place(&cipher, nonce, &mut buffer).unwrap();
+
+            let (padding, ciphertext) = buffer.split_at(legacy::BOXZEROBYTES);
+            assert_eq!(padding, [0u8; legacy::BOXZEROBYTES]);
+            assert_eq!($ciphertext, ciphertext);
+        }
+
+        #[test]
+        fn legacy_decrypt_in_place() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+            let mut buffer = vec![0u8; legacy::BOXZEROBYTES];
+            buffer.extend_from_slice($ciphertext);
+            legacy::decrypt_in_place(&cipher, nonce, &mut buffer).unwrap();
+
+            let (padding, plaintext) = buffer.split_at(legacy::ZEROBYTES);
+            assert_eq!(padding, [0u8; legacy::ZEROBYTES]);
+            assert_eq!(PLAINTEXT, plaintext);
+        }
+
+        #[test]
+        fn legacy_nonzero_padding() {
+            let key = GenericArray::from_slice(KEY);
+            let nonce = GenericArray::from_slice(NONCE);
+            let cipher = <$cipher>::new(key);
+
+            let mut buffer = vec![0u8; legacy::ZEROBYTES];
+            buffer.extend_from_slice(PLAINTEXT);
+            buffer[legacy::ZEROBYTES - 1] = 1;
+            assert!(legacy::encrypt_in_place(&cipher, nonce, &mut buffer).is_err());
+
+            let mut buffer = vec![0u8; legacy::BOXZEROBYTES];
+            buffer.extend_from_slice($ciphertext);
+            buffer[0] = 1;
+            assert!(legacy::decrypt_in_place(&cipher, nonce, &mut buffer).is_err());
+        }
+
         #[test]
         fn decrypt_modified() {
             let key = GenericArray::from_slice(KEY);
@@ -60,8 +322,8 @@ macro_rules! impl_tests {
 mod xchacha20poly1305 {
     use super::{KEY, NONCE, PLAINTEXT};
     use crypto_secretbox::{
-        aead::{generic_array::GenericArray, Aead, KeyInit},
-        XChaCha20Poly1305,
+        aead::{generic_array::GenericArray, Aead, Error, KeyInit},
+        legacy, XChaCha20Poly1305,
     };
     use hex_literal::hex;
 
@@ -79,8 +341,8 @@ mod xchacha20poly1305 {
 mod xsalsa20poly1305 {
     use super::{KEY, NONCE, PLAINTEXT};
     use crypto_secretbox::{
-        aead::{generic_array::GenericArray, Aead, KeyInit},
-        XSalsa20Poly1305,
+        aead::{generic_array::GenericArray, Aead, Error, KeyInit},
+        legacy, XSalsa20Poly1305,
     };
     use hex_literal::hex;
 
@@ -93,3 +355,59 @@ mod xsalsa20poly1305 {
 
     impl_tests!(XSalsa20Poly1305, CIPHERTEXT);
 }
+
+#[cfg(feature = "salsa20")]
+mod secret_key {
+    use super::{KEY, NONCE, PLAINTEXT};
+    use crypto_secretbox::{
+        aead::{generic_array::GenericArray, Aead, KeyInit},
+        SecretKey, XSalsa20Poly1305,
+    };
+
+    #[test]
+    fn from_bytes() {
+        let key = SecretKey::from(*KEY);
+        assert_eq!(key.as_bytes(), KEY);
+        assert_eq!(key, SecretKey::from_slice(KEY).unwrap());
+        assert_ne!(key, SecretKey::from([0u8; 32]));
+        assert!(SecretKey::from_slice(&KEY[..31]).is_err());
+
+        // Ensure `Debug` impl on `SecretKey` is covered in tests
+        assert_eq!(format!("{:?}", key), "SecretKey { .. }");
+    }
+
+    #[test]
+    fn secretbox_from_secret_key() {
+        let key = SecretKey::from(*KEY);
+        let nonce = GenericArray::from_slice(NONCE);
+        let expected = XSalsa20Poly1305::new(key.as_ref())
+            .encrypt(nonce, PLAINTEXT)
+            .unwrap();
+
+        let cipher = XSalsa20Poly1305::from(&key);
+        assert_eq!(cipher.encrypt(nonce, PLAINTEXT).unwrap(), expected);
+
+        let cipher = XSalsa20Poly1305::from(key);
+        assert_eq!(cipher.encrypt(nonce, PLAINTEXT).unwrap(), expected);
+    }
+
+    #[cfg(feature = "getrandom")]
+    #[test]
+    fn generate() {
+        use crypto_secretbox::aead::OsRng;
+        assert_ne!(
+            SecretKey::generate(&mut OsRng),
+            SecretKey::generate(&mut OsRng)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialization() {
+        let key = SecretKey::from(*KEY);
+
+        let serialized = bincode::serialize(&key).unwrap();
+        let deserialized: SecretKey = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, key);
+    }
+}
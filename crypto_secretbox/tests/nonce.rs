@@ -0,0 +1,91 @@
+//! Nonce management tests.
+
+use crypto_secretbox::{
+    nonce::{self, Checkpoint, NonceSequence},
+    Nonce,
+};
+use hex_literal::hex;
+
+const PREFIX: [u8; 16] = hex!("69696ee955b62b73cd62bda875fc73d6");
+
+#[test]
+fn increment() {
+    let mut n = [0u8; 4];
+    nonce::increment(&mut n);
+    assert_eq!(n, [1, 0, 0, 0]);
+
+    let mut n = [0xff, 0xff, 0x00, 0x01];
+    nonce::increment(&mut n);
+    assert_eq!(n, [0x00, 0x00, 0x01, 0x01]);
+
+    let mut n = [0xff; 4];
+    nonce::increment(&mut n);
+    assert_eq!(n, [0x00; 4]);
+}
+
+#[test]
+fn sequence() {
+    let mut sequence = NonceSequence::new(PREFIX);
+
+    let first = sequence.next_nonce().unwrap();
+    assert_eq!(
+        first.as_slice(),
+        hex!("000000000000000069696ee955b62b73cd62bda875fc73d6")
+    );
+
+    let mut expected = first;
+    nonce::increment(&mut expected);
+    assert_eq!(sequence.next_nonce().unwrap(), expected);
+    assert_eq!(sequence.counter(), 2);
+}
+
+#[test]
+fn sequence_exhausted() {
+    let mut checkpoint = NonceSequence::new(PREFIX).checkpoint().to_bytes();
+    checkpoint[..8].copy_from_slice(&(u64::MAX - 1).to_le_bytes());
+
+    let mut sequence = NonceSequence::resume(&Checkpoint::from(checkpoint), 0).unwrap();
+    assert!(sequence.next_nonce().is_ok());
+    assert!(sequence.next_nonce().is_err());
+    assert!(sequence.next_nonce().is_err());
+
+    assert!(NonceSequence::resume(&Checkpoint::from(checkpoint), 2).is_err());
+}
+
+#[test]
+fn checkpoint_and_resume() {
+    let mut sequence = NonceSequence::new(PREFIX);
+    sequence.next_nonce().unwrap();
+
+    let checkpoint = Checkpoint::from_bytes(sequence.checkpoint().to_bytes());
+    assert_eq!(NonceSequence::resume(&checkpoint, 0).unwrap(), sequence);
+
+    let mut resumed = NonceSequence::resume(&checkpoint, 1000).unwrap();
+    assert_eq!(resumed.counter(), 1001);
+
+    let nonce: Nonce = resumed.next_nonce().unwrap();
+    assert_eq!(&nonce[..8], &1001u64.to_le_bytes());
+    assert_eq!(&nonce[8..], &PREFIX);
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn generate() {
+    use crypto_secretbox::aead::OsRng;
+
+    assert_ne!(nonce::generate(&mut OsRng), nonce::generate(&mut OsRng));
+    assert_ne!(
+        NonceSequence::generate(&mut OsRng).next_nonce(),
+        NonceSequence::generate(&mut OsRng).next_nonce()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn checkpoint_serialization() {
+    let checkpoint = NonceSequence::new(PREFIX).checkpoint();
+
+    let serialized = bincode::serialize(&checkpoint).unwrap();
+    let deserialized: Checkpoint = bincode::deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, checkpoint);
+}
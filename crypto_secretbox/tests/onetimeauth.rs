@@ -0,0 +1,64 @@
+//! `crypto_onetimeauth` tests.
+//!
+//! NaCl test vector adapted from `tests/onetimeauth.c` and
+//! `tests/onetimeauth.out`; RFC 8439 test vector from section 2.5.2.
+
+use crypto_secretbox::onetimeauth::{self, State};
+use hex_literal::hex;
+
+const NACL_KEY: &[u8; 32] =
+    &hex!("eea6a7251c1e72916d11c2cb214d3c252539121d8e234e652d651fa4c8cff880");
+const NACL_MESSAGE: &[u8] = &hex!(
+    "8e993b9f48681273c29650ba32fc76ce48332ea7164d96a4476fb8c531a1186ac0dfc17c98dce87b"
+    "4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738b48eeee314a7cc8ab932164548e526ae"
+    "90224368517acfeabd6bb3732bc0e9da99832b61ca01b6de56244a9e88d5f9b37973f622a43d14a6"
+    "599b1f654cb45a74e355a5"
+);
+const NACL_TAG: &[u8; 16] = &hex!("f3ffc7703f9400e52a7dfb4b3d3305d9");
+
+const RFC8439_KEY: &[u8; 32] =
+    &hex!("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b");
+const RFC8439_MESSAGE: &[u8] = b"Cryptographic Forum Research Group";
+const RFC8439_TAG: &[u8; 16] = &hex!("a8061dc1305136c6c22b8baf0c0127a9");
+
+#[test]
+fn auth() {
+    let tag = onetimeauth::auth(NACL_KEY.into(), NACL_MESSAGE);
+    assert_eq!(NACL_TAG, tag.as_slice());
+
+    let tag = onetimeauth::auth(RFC8439_KEY.into(), RFC8439_MESSAGE);
+    assert_eq!(RFC8439_TAG, tag.as_slice());
+}
+
+#[test]
+fn verify() {
+    assert!(onetimeauth::verify(NACL_KEY.into(), NACL_MESSAGE, NACL_TAG.into()).is_ok());
+
+    let mut message = NACL_MESSAGE.to_vec();
+    *message.last_mut().unwrap() ^= 0xaa;
+    assert!(onetimeauth::verify(NACL_KEY.into(), &message, NACL_TAG.into()).is_err());
+
+    let mut tag = *NACL_TAG;
+    tag[0] ^= 0xaa;
+    assert!(onetimeauth::verify(NACL_KEY.into(), NACL_MESSAGE, &tag.into()).is_err());
+}
+
+#[test]
+fn incremental() {
+    for chunk_size in [1, 7, 16, 17, 64] {
+        let mut state = State::new(NACL_KEY.into());
+
+        for chunk in NACL_MESSAGE.chunks(chunk_size) {
+            state.update(chunk);
+        }
+
+        assert!(state.clone().verify(NACL_TAG.into()).is_ok());
+        assert_eq!(NACL_TAG, state.finalize().as_slice());
+    }
+}
+
+#[test]
+fn empty_message() {
+    let tag = onetimeauth::auth(NACL_KEY.into(), b"");
+    assert_eq!(tag, State::new(NACL_KEY.into()).finalize());
+}
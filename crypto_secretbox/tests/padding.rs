@@ -0,0 +1,180 @@
+//! Length-hiding padding tests.
+
+#![cfg(all(feature = "alloc", feature = "salsa20"))]
+
+use crypto_secretbox::{
+    aead::{Aead, AeadInPlace, KeyInit},
+    padding::{self, Padding},
+    Nonce, XSalsa20Poly1305,
+};
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const NONCE: &[u8; 24] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+
+fn padded(message: &[u8], padding: Padding) -> Vec<u8> {
+    let mut buffer = message.to_vec();
+    padding::pad(&mut buffer, padding).unwrap();
+    buffer
+}
+
+#[test]
+fn pad_block() {
+    assert_eq!(
+        padded(b"hello", Padding::Block(16)),
+        hex!("68656c6c6f8000000000000000000000")
+    );
+    assert_eq!(padded(b"hello", Padding::Block(1)), hex!("68656c6c6f80"));
+    assert_eq!(padded(b"", Padding::Block(4)), hex!("80000000"));
+
+    // A full block is added to messages which are already block-aligned
+    let message = [0xffu8; 16];
+    let buffer = padded(&message, Padding::Block(16));
+    assert_eq!(buffer.len(), 32);
+    assert_eq!(buffer[..16], message);
+    assert_eq!(buffer[16..], hex!("80000000000000000000000000000000"));
+}
+
+#[test]
+fn padded_len() {
+    assert_eq!(Padding::Block(16).padded_len(0), Some(16));
+    assert_eq!(Padding::Block(16).padded_len(15), Some(16));
+    assert_eq!(Padding::Block(16).padded_len(16), Some(32));
+    assert_eq!(Padding::Block(0).padded_len(16), None);
+    assert_eq!(Padding::Block(16).padded_len(usize::MAX), None);
+
+    assert_eq!(Padding::Padme.padded_len(0), Some(1));
+    assert_eq!(Padding::Padme.padded_len(8), Some(10));
+    assert_eq!(Padding::Padme.padded_len(99), Some(104));
+    assert_eq!(Padding::Padme.padded_len(999), Some(1024));
+    assert_eq!(Padding::Padme.padded_len(usize::MAX), None);
+}
+
+#[test]
+fn padme_overhead() {
+    for len in 0..10_000 {
+        let padded_len = Padding::Padme.padded_len(len).unwrap();
+        assert!(padded_len > len);
+        assert!(padded_len - len - 1 <= (len + 1) * 12 / 100);
+    }
+}
+
+#[test]
+fn round_trip() {
+    for padding in [
+        Padding::Block(1),
+        Padding::Block(16),
+        Padding::Block(100),
+        Padding::Padme,
+    ] {
+        for len in 0..300 {
+            let message: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut buffer = padded(&message, padding);
+            assert_eq!(Some(buffer.len()), padding.padded_len(len));
+            assert_eq!(padding::unpadded_len(&buffer, padding), Ok(len));
+
+            padding::unpad(&mut buffer, padding).unwrap();
+            assert_eq!(message, buffer);
+        }
+    }
+}
+
+#[test]
+fn unpad_invalid() {
+    for (buffer, padding) in [
+        // No marker
+        (&hex!("00000000")[..], Padding::Block(4)),
+        (&hex!("00000000")[..], Padding::Padme),
+        (&hex!("")[..], Padding::Padme),
+        // Non-zero byte after the marker
+        (&hex!("68800100")[..], Padding::Block(4)),
+        (&hex!("68800100")[..], Padding::Padme),
+        // Marker outside of the last block
+        (&hex!("688000000000")[..], Padding::Block(4)),
+        // Shorter than a block
+        (&hex!("688000")[..], Padding::Block(4)),
+        // Zero block size
+        (&hex!("68800000")[..], Padding::Block(0)),
+    ] {
+        let mut vec = buffer.to_vec();
+        assert!(padding::unpad(&mut vec, padding).is_err());
+        assert_eq!(buffer, vec);
+    }
+}
+
+#[test]
+fn encrypt_padded() {
+    let cipher = XSalsa20Poly1305::new(KEY.into());
+    let nonce = Nonce::from_slice(NONCE);
+    let plaintext = b"short chat message";
+
+    let ciphertext = cipher
+        .encrypt_padded(nonce, plaintext, Padding::Block(64))
+        .unwrap();
+    assert_eq!(ciphertext.len(), XSalsa20Poly1305::TAG_SIZE + 64);
+    assert_eq!(
+        ciphertext,
+        cipher
+            .encrypt(nonce, padded(plaintext, Padding::Block(64)).as_slice())
+            .unwrap()
+    );
+
+    assert_eq!(
+        cipher
+            .decrypt_padded(nonce, &ciphertext, Padding::Block(64))
+            .unwrap(),
+        plaintext
+    );
+}
+
+#[test]
+fn decrypt_padded_invalid() {
+    let cipher = XSalsa20Poly1305::new(KEY.into());
+    let nonce = Nonce::from_slice(NONCE);
+
+    let mut ciphertext = cipher
+        .encrypt_padded(nonce, b"message", Padding::Padme)
+        .unwrap();
+
+    // Authentic ciphertext without padding
+    let unpadded = cipher.encrypt(nonce, &b"message"[..]).unwrap();
+    assert!(cipher
+        .decrypt_padded(nonce, &unpadded, Padding::Padme)
+        .is_err());
+
+    ciphertext[20] ^= 0x80;
+    assert!(cipher
+        .decrypt_padded(nonce, &ciphertext, Padding::Padme)
+        .is_err());
+}
+
+#[test]
+fn decrypt_padded_in_place_invalid_padding() {
+    let cipher = XSalsa20Poly1305::new(KEY.into());
+    let nonce = Nonce::from_slice(NONCE);
+
+    // Authentic ciphertext without padding
+    let mut buffer = b"message".to_vec();
+    cipher.encrypt_in_place(nonce, b"", &mut buffer).unwrap();
+
+    assert!(cipher
+        .decrypt_padded_in_place(nonce, &mut buffer, Padding::Padme)
+        .is_err());
+    assert!(buffer.is_empty());
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn encrypt_padded_in_place_insufficient_capacity() {
+    use crypto_secretbox::aead::heapless::Vec;
+
+    let cipher = XSalsa20Poly1305::new(KEY.into());
+    let nonce = Nonce::from_slice(NONCE);
+
+    // Room for the padding, but not the tag
+    let mut buffer = Vec::<u8, 64>::from_slice(b"message").unwrap();
+    assert!(cipher
+        .encrypt_padded_in_place(nonce, &mut buffer, Padding::Block(64))
+        .is_err());
+    assert_eq!(buffer, b"message");
+}
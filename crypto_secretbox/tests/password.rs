@@ -0,0 +1,192 @@
+//! Password-based encryption tests.
+//!
+//! libsodium test vectors generated with `crypto_pwhash` and
+//! `crypto_secretbox_easy` using `test-vector-gen`.
+
+#![cfg(all(feature = "argon2", feature = "salsa20"))]
+
+use crypto_secretbox::{
+    aead::Aead,
+    password::{self, Params, HEADER_SIZE, VERSION},
+    Nonce, XSalsa20Poly1305,
+};
+use hex_literal::hex;
+
+const PASSWORD: &[u8] = b"correct horse battery staple";
+const SALT: &[u8; 16] = &hex!("5c1d7e209a43f10b6d882ec439a752ee");
+const NONCE: &[u8; 24] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+const PLAINTEXT: &[u8] = b"backup encrypted with a passphrase";
+const OPS_LIMIT: u32 = 3;
+const MEM_LIMIT: usize = 1024 * 1024;
+
+const KEY: &[u8; 32] = &hex!("6d5a71bbc0acbe412ac477e861ec38db57916f4c1cbfe767284d6f876c00c101");
+const CIPHERTEXT: &[u8] = &hex!(
+    "4789e8bc32a4b56f020053a76f6043c286ebeffd4a773fbc91e8c198425d8ec3"
+    "7c5daf35949285c39385a21a794744c73a06"
+);
+
+// More passes than any preset, but little memory
+const HIGH_OPS_LIMIT: u32 = 10;
+const HIGH_OPS_MEM_LIMIT: usize = 64 * 1024;
+const HIGH_OPS_CIPHERTEXT: &[u8] = &hex!(
+    "92d76da8ae5cf6619ae58631ef249c5a0a64d58a50ad3acaa7f3dcaab7832ea7"
+    "c5e8fe5ba0c4762e7c635e1b85d74b21eb50"
+);
+
+fn params() -> Params {
+    Params::new(OPS_LIMIT, MEM_LIMIT).unwrap()
+}
+
+/// Build a blob from the output of libsodium's `crypto_pwhash` +
+/// `crypto_secretbox_easy` sequence.
+fn libsodium_blob() -> Vec<u8> {
+    let mut blob = vec![VERSION];
+    blob.extend_from_slice(&OPS_LIMIT.to_le_bytes());
+    blob.extend_from_slice(&((MEM_LIMIT / 1024) as u32).to_le_bytes());
+    blob.extend_from_slice(SALT);
+    blob.extend_from_slice(NONCE);
+    blob.extend_from_slice(CIPHERTEXT);
+    blob
+}
+
+#[test]
+fn presets() {
+    assert_eq!(Params::INTERACTIVE.ops_limit(), 2);
+    assert_eq!(Params::INTERACTIVE.mem_limit(), 67108864);
+    assert_eq!(Params::MODERATE.ops_limit(), 3);
+    assert_eq!(Params::MODERATE.mem_limit(), 268435456);
+    assert_eq!(Params::SENSITIVE.ops_limit(), 4);
+    assert_eq!(Params::SENSITIVE.mem_limit(), 1073741824);
+}
+
+#[test]
+fn invalid_params() {
+    assert!(Params::new(0, MEM_LIMIT).is_err());
+    assert!(Params::new(1, 8191).is_err());
+    assert_eq!(Params::new(1, 8192 + 1023).unwrap().mem_limit(), 8192);
+}
+
+#[test]
+fn derive_key() {
+    let key = password::derive_key(PASSWORD, SALT, params()).unwrap();
+    assert_eq!(key.as_bytes(), KEY);
+}
+
+#[test]
+fn from_password() {
+    let cipher = XSalsa20Poly1305::from_password(PASSWORD, SALT, params()).unwrap();
+    let nonce = Nonce::from_slice(NONCE);
+    assert_eq!(cipher.encrypt(nonce, PLAINTEXT).unwrap(), CIPHERTEXT);
+    assert_eq!(cipher.decrypt(nonce, CIPHERTEXT).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn decrypt_libsodium_blob() {
+    let blob = libsodium_blob();
+    assert_eq!(blob.len(), HEADER_SIZE + CIPHERTEXT.len());
+    assert_eq!(
+        XSalsa20Poly1305::decrypt_with_password(PASSWORD, &blob).unwrap(),
+        PLAINTEXT
+    );
+    assert!(XSalsa20Poly1305::decrypt_with_password(b"wrong password", &blob).is_err());
+}
+
+#[test]
+fn decrypt_libsodium_blob_high_ops() {
+    let mut blob = vec![VERSION];
+    blob.extend_from_slice(&HIGH_OPS_LIMIT.to_le_bytes());
+    blob.extend_from_slice(&((HIGH_OPS_MEM_LIMIT / 1024) as u32).to_le_bytes());
+    blob.extend_from_slice(SALT);
+    blob.extend_from_slice(NONCE);
+    blob.extend_from_slice(HIGH_OPS_CIPHERTEXT);
+
+    assert_eq!(
+        XSalsa20Poly1305::decrypt_with_password(PASSWORD, &blob).unwrap(),
+        PLAINTEXT
+    );
+}
+
+#[test]
+fn invalid_blob() {
+    let blob = libsodium_blob();
+
+    let mut bad_version = blob.clone();
+    bad_version[0] = VERSION + 1;
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &bad_version).is_err());
+
+    let mut bad_salt = blob.clone();
+    bad_salt[9] ^= 1;
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &bad_salt).is_err());
+
+    let mut bad_ops_limit = blob.clone();
+    bad_ops_limit[1..5].copy_from_slice(&0u32.to_le_bytes());
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &bad_ops_limit).is_err());
+
+    // Cost parameters beyond `Params::SENSITIVE` are rejected up front,
+    // whether due to memory or to the total cost
+    let mut too_much_memory = blob.clone();
+    too_much_memory[1..5].copy_from_slice(&1u32.to_le_bytes());
+    too_much_memory[5..9].copy_from_slice(&(2 * 1024 * 1024u32).to_le_bytes());
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &too_much_memory).is_err());
+
+    let mut too_costly = blob.clone();
+    too_costly[1..5].copy_from_slice(&5u32.to_le_bytes());
+    too_costly[5..9].copy_from_slice(&(1024 * 1024u32).to_le_bytes());
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &too_costly).is_err());
+
+    assert!(XSalsa20Poly1305::decrypt_with_password(PASSWORD, &blob[..HEADER_SIZE - 1]).is_err());
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn encrypt_with_password() {
+    use crypto_secretbox::aead::OsRng;
+
+    let blob =
+        XSalsa20Poly1305::encrypt_with_password(PASSWORD, &mut OsRng, PLAINTEXT, params()).unwrap();
+    assert_eq!(blob[0], VERSION);
+    assert_eq!(blob[1..5], OPS_LIMIT.to_le_bytes());
+    assert_eq!(blob[5..9], ((MEM_LIMIT / 1024) as u32).to_le_bytes());
+    assert_eq!(blob.len(), HEADER_SIZE + 16 + PLAINTEXT.len());
+
+    assert_eq!(
+        XSalsa20Poly1305::decrypt_with_password(PASSWORD, &blob).unwrap(),
+        PLAINTEXT
+    );
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn encrypt_with_custom_params() {
+    use crypto_secretbox::aead::OsRng;
+
+    for (ops_limit, mem_limit) in [(1, 8 * 1024), (HIGH_OPS_LIMIT, HIGH_OPS_MEM_LIMIT)] {
+        let params = Params::new(ops_limit, mem_limit).unwrap();
+        let blob = XSalsa20Poly1305::encrypt_with_password(PASSWORD, &mut OsRng, PLAINTEXT, params)
+            .unwrap();
+        assert_eq!(blob[1..5], ops_limit.to_le_bytes());
+        assert_eq!(blob[5..9], ((mem_limit / 1024) as u32).to_le_bytes());
+
+        assert_eq!(
+            XSalsa20Poly1305::decrypt_with_password(PASSWORD, &blob).unwrap(),
+            PLAINTEXT
+        );
+    }
+}
+
+#[cfg(feature = "getrandom")]
+#[test]
+fn encrypt_with_password_too_costly() {
+    use crypto_secretbox::aead::OsRng;
+
+    // Blobs beyond `Params::SENSITIVE` couldn't be decrypted, so they aren't produced
+    for params in [
+        Params::new(5, 1 << 30).unwrap(),
+        Params::new(1, 2 << 30).unwrap(),
+    ] {
+        assert!(
+            XSalsa20Poly1305::encrypt_with_password(PASSWORD, &mut OsRng, PLAINTEXT, params)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Seekable file format tests.
+
+#![cfg(all(feature = "salsa20", feature = "std", feature = "stream"))]
+
+use crypto_secretbox::{
+    aead::{consts::U19, generic_array::GenericArray, KeyInit},
+    seekable::{SeekableReader, SeekableWriter, HEADER_SIZE, VERSION},
+    stream::StreamWriter,
+    XSalsa20Poly1305,
+};
+use hex_literal::hex;
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const NONCE: &[u8; 19] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0");
+const CHUNK_SIZE: usize = 64;
+const SEGMENT_SIZE: usize = CHUNK_SIZE + 16;
+
+fn cipher() -> XSalsa20Poly1305 {
+    XSalsa20Poly1305::new(KEY.into())
+}
+
+fn nonce() -> &'static GenericArray<u8, U19> {
+    NONCE.into()
+}
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+    let mut writer = SeekableWriter::new(cipher(), nonce(), CHUNK_SIZE as u32, Vec::new()).unwrap();
+
+    // Write in odd-sized pieces to exercise chunk buffering
+    for piece in plaintext.chunks(7) {
+        writer.write_all(piece).unwrap();
+    }
+
+    writer.finish().unwrap()
+}
+
+#[test]
+fn round_trip() {
+    for len in [
+        0,
+        1,
+        CHUNK_SIZE - 1,
+        CHUNK_SIZE,
+        CHUNK_SIZE + 1,
+        5 * CHUNK_SIZE,
+        5 * CHUNK_SIZE + 3,
+    ] {
+        let plaintext = plaintext(len);
+        let mut reader = SeekableReader::new(cipher(), Cursor::new(encrypt(&plaintext))).unwrap();
+        assert_eq!(reader.len(), len as u64);
+
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted).unwrap();
+        assert_eq!(plaintext, decrypted);
+    }
+}
+
+#[test]
+fn layout() {
+    let plaintext = plaintext(3 * CHUNK_SIZE + 10);
+    let ciphertext = encrypt(&plaintext);
+
+    let (header, body) = ciphertext.split_at(HEADER_SIZE);
+    assert_eq!(header[0], VERSION);
+    assert_eq!(header[1..5], (CHUNK_SIZE as u32).to_le_bytes());
+    assert_eq!(&header[5..], NONCE);
+    assert_eq!(body.len(), plaintext.len() + 4 * 16);
+
+    // Chunks are encrypted under a key bound to the header, not the key itself
+    let mut writer = StreamWriter::new(cipher(), nonce(), CHUNK_SIZE, Vec::new());
+    writer.write_all(&plaintext).unwrap();
+    assert_ne!(body, writer.finish().unwrap());
+}
+
+#[test]
+fn random_access() {
+    let plaintext = plaintext(5 * CHUNK_SIZE + 3);
+    let mut reader = SeekableReader::new(cipher(), Cursor::new(encrypt(&plaintext))).unwrap();
+
+    for (start, len) in [
+        (0, 10),
+        (CHUNK_SIZE - 2, 4),
+        (130, 100),
+        (5 * CHUNK_SIZE, 3),
+    ] {
+        reader.seek(SeekFrom::Start(start as u64)).unwrap();
+        let mut buf = vec![0u8; len];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&plaintext[start..][..len], buf.as_slice());
+    }
+
+    assert_eq!(
+        reader.seek(SeekFrom::End(-1)).unwrap(),
+        plaintext.len() as u64 - 1
+    );
+    assert_eq!(
+        reader.seek(SeekFrom::Current(-9)).unwrap(),
+        plaintext.len() as u64 - 10
+    );
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(&plaintext[plaintext.len() - 10..], rest.as_slice());
+
+    // Reading past the end returns EOF
+    reader.seek(SeekFrom::End(10)).unwrap();
+    assert_eq!(reader.read(&mut [0u8; 1]).unwrap(), 0);
+
+    assert_eq!(
+        reader.seek(SeekFrom::Current(-1000)).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn range_read_with_corrupt_other_chunk() {
+    let plaintext = plaintext(5 * CHUNK_SIZE + 3);
+    let mut ciphertext = encrypt(&plaintext);
+
+    // Corrupt chunk 3, which is never read
+    ciphertext[HEADER_SIZE + 3 * SEGMENT_SIZE] ^= 0xaa;
+
+    let mut reader = SeekableReader::new(cipher(), Cursor::new(ciphertext)).unwrap();
+    reader.seek(SeekFrom::Start(CHUNK_SIZE as u64)).unwrap();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&plaintext[CHUNK_SIZE..][..CHUNK_SIZE], buf.as_slice());
+
+    reader.seek(SeekFrom::Start(3 * CHUNK_SIZE as u64)).unwrap();
+    assert_eq!(
+        reader.read(&mut buf).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn truncated() {
+    let plaintext = plaintext(5 * CHUNK_SIZE);
+    let ciphertext = encrypt(&plaintext);
+
+    // Drop the last (empty) chunk, a whole chunk, or a single byte
+    for len in [
+        ciphertext.len() - 16,
+        ciphertext.len() - 16 - SEGMENT_SIZE,
+        ciphertext.len() - 1,
+        HEADER_SIZE,
+        HEADER_SIZE - 1,
+    ] {
+        let err = SeekableReader::new(cipher(), Cursor::new(ciphertext[..len].to_vec()))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn reordered() {
+    let plaintext = plaintext(5 * CHUNK_SIZE + 3);
+    let mut ciphertext = encrypt(&plaintext);
+
+    // Swap chunks 1 and 2
+    let (first, second) = ciphertext[HEADER_SIZE + SEGMENT_SIZE..].split_at_mut(SEGMENT_SIZE);
+    first.swap_with_slice(&mut second[..SEGMENT_SIZE]);
+
+    let mut reader = SeekableReader::new(cipher(), Cursor::new(ciphertext)).unwrap();
+    reader.seek(SeekFrom::Start(CHUNK_SIZE as u64)).unwrap();
+    assert_eq!(
+        reader.read(&mut [0u8; 1]).unwrap_err().kind(),
+        ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn invalid_header() {
+    let ciphertext = encrypt(&plaintext(3 * CHUNK_SIZE));
+
+    let mut bad_version = ciphertext.clone();
+    bad_version[0] = VERSION + 1;
+    assert!(SeekableReader::new(cipher(), Cursor::new(bad_version)).is_err());
+
+    let mut bad_chunk_size = ciphertext.clone();
+    bad_chunk_size[1] ^= 1;
+    assert!(SeekableReader::new(cipher(), Cursor::new(bad_chunk_size)).is_err());
+
+    let mut zero_chunk_size = ciphertext;
+    zero_chunk_size[1..5].copy_from_slice(&[0; 4]);
+    assert!(SeekableReader::new(cipher(), Cursor::new(zero_chunk_size)).is_err());
+}
+
+#[test]
+fn modified_header_single_chunk() {
+    // Changing the chunk size of a single-chunk file doesn't move any chunk
+    // boundaries, so only the header binding catches it
+    let ciphertext = encrypt(&plaintext(10));
+    assert!(SeekableReader::new(cipher(), Cursor::new(ciphertext.clone())).is_ok());
+
+    for i in 1..5 {
+        let mut bad_chunk_size = ciphertext.clone();
+        bad_chunk_size[i] ^= 0x80;
+        let err = SeekableReader::new(cipher(), Cursor::new(bad_chunk_size))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    for i in 5..HEADER_SIZE {
+        let mut bad_nonce = ciphertext.clone();
+        bad_nonce[i] ^= 1;
+        assert!(SeekableReader::new(cipher(), Cursor::new(bad_nonce)).is_err());
+    }
+}
+
+#[test]
+fn wrong_key() {
+    let ciphertext = encrypt(&plaintext(10));
+    let cipher = XSalsa20Poly1305::new(&[0u8; 32].into());
+    assert!(SeekableReader::new(cipher, Cursor::new(ciphertext)).is_err());
+}
@@ -0,0 +1,144 @@
+//! Synthetic nonce SecretBox tests.
+//!
+//! Test vectors are regression vectors generated by this implementation.
+
+#![cfg(all(feature = "alloc", any(feature = "chacha20", feature = "salsa20")))]
+
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const CONTEXT: &[u8] = b"context";
+const PLAINTEXT: &[u8] = b"synthetic nonce test message";
+
+macro_rules! impl_tests {
+    ($cipher:path, $empty:expr, $no_context:expr, $with_context:expr) => {
+        #[test]
+        fn encrypt() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+            assert_eq!(cipher.encrypt(b"", b"").unwrap(), $empty);
+            assert_eq!(cipher.encrypt(b"", PLAINTEXT).unwrap(), $no_context);
+            assert_eq!(cipher.encrypt(CONTEXT, PLAINTEXT).unwrap(), $with_context);
+        }
+
+        #[test]
+        fn decrypt() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+            assert_eq!(cipher.decrypt(b"", $empty).unwrap(), b"");
+            assert_eq!(cipher.decrypt(b"", $no_context).unwrap(), PLAINTEXT);
+            assert_eq!(cipher.decrypt(CONTEXT, $with_context).unwrap(), PLAINTEXT);
+        }
+
+        #[test]
+        fn encrypt_in_place_detached() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+            let mut buffer = PLAINTEXT.to_vec();
+            let (nonce, tag) = cipher
+                .encrypt_in_place_detached(CONTEXT, &mut buffer)
+                .unwrap();
+
+            assert_eq!(&$with_context[..24], nonce.as_slice());
+            assert_eq!(&$with_context[24..40], tag.as_slice());
+            assert_eq!(&$with_context[40..], buffer.as_slice());
+
+            cipher
+                .decrypt_in_place_detached(CONTEXT, &mut buffer, &nonce, &tag)
+                .unwrap();
+            assert_eq!(PLAINTEXT, buffer.as_slice());
+        }
+
+        #[test]
+        fn deterministic() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+            let ciphertext = cipher.encrypt(CONTEXT, PLAINTEXT).unwrap();
+            assert_eq!(ciphertext, cipher.encrypt(CONTEXT, PLAINTEXT).unwrap());
+
+            // Changing the message or context changes the synthetic nonce
+            let other = cipher
+                .encrypt(CONTEXT, b"synthetic nonce test messagf")
+                .unwrap();
+            assert_ne!(ciphertext[..24], other[..24]);
+            let other = cipher.encrypt(b"contexu", PLAINTEXT).unwrap();
+            assert_ne!(ciphertext[..24], other[..24]);
+        }
+
+        #[test]
+        fn decrypt_wrong_context() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+            assert!(cipher.decrypt(b"", $with_context).is_err());
+            assert!(cipher.decrypt(CONTEXT, $no_context).is_err());
+
+            // Plaintext isn't released when only the context is wrong
+            let mut buffer = $with_context[40..].to_vec();
+            let nonce = GenericArray::from_slice(&$with_context[..24]);
+            let tag = GenericArray::from_slice(&$with_context[24..40]);
+            assert!(cipher
+                .decrypt_in_place_detached(b"", &mut buffer, nonce, tag)
+                .is_err());
+            assert_eq!(buffer, vec![0u8; PLAINTEXT.len()]);
+        }
+
+        #[test]
+        fn decrypt_modified() {
+            let cipher = <$cipher>::new(GenericArray::from_slice(KEY));
+
+            // Tweak the nonce, tag, and ciphertext in turn
+            for i in [0, 23, 24, 39, 40, $with_context.len() - 1] {
+                let mut ciphertext = $with_context.to_vec();
+                ciphertext[i] ^= 0xaa;
+                assert!(cipher.decrypt(CONTEXT, &ciphertext).is_err());
+            }
+
+            assert!(cipher.decrypt(CONTEXT, &$with_context[..39]).is_err());
+        }
+    };
+}
+
+#[cfg(feature = "chacha20")]
+mod xchacha20poly1305 {
+    use super::{CONTEXT, KEY, PLAINTEXT};
+    use crypto_secretbox::{
+        aead::{generic_array::GenericArray, KeyInit},
+        XChaCha20Poly1305Siv,
+    };
+    use hex_literal::hex;
+
+    const EMPTY: &[u8] =
+        &hex!("ca0978ff507184181fc8066d5896621c22c7134956213c4e7354c060651a931423ee4116ace51112");
+
+    const NO_CONTEXT: &[u8] = &hex!(
+        "87a42f83740afcd63fe22b656c481e104c67cc932866a156641cbaa5567abd654a169ce36a693bd1"
+        "59964651fd8a05cdb6058fcc18773ddfa10a659b8cce61d2b0a28914"
+    );
+
+    const WITH_CONTEXT: &[u8] = &hex!(
+        "73cf42afa9c3ff7ac1ae4aaea19b073aca674c6990070e73b10b2804f2692463589d0d85b0ae37cc"
+        "acda066c99371336e155eab0e1085ff3dd48c1375a11ce4e5789ccff"
+    );
+
+    impl_tests!(XChaCha20Poly1305Siv, EMPTY, NO_CONTEXT, WITH_CONTEXT);
+}
+
+#[cfg(feature = "salsa20")]
+mod xsalsa20poly1305 {
+    use super::{CONTEXT, KEY, PLAINTEXT};
+    use crypto_secretbox::{
+        aead::{generic_array::GenericArray, KeyInit},
+        XSalsa20Poly1305Siv,
+    };
+    use hex_literal::hex;
+
+    const EMPTY: &[u8] =
+        &hex!("b924222a804f7bf5fef1bf14b113a22f23bf55ff0276db9494a39d38eb950c4f6fdc7de414676944");
+
+    const NO_CONTEXT: &[u8] = &hex!(
+        "d709ddaaeb362b2cbbcfaedd452013fe45aed1e0f16911174857eacb5317b261680dfef02b5b8871"
+        "72a39097e356baead805d80e8e49bcc1fbd47d65806f9db0a5676a4d"
+    );
+
+    const WITH_CONTEXT: &[u8] = &hex!(
+        "cc95ae62b559d62e85eb2960a1c7e50de020fcc0b165e8ef4369462fab0026c0b43a5a1ed93c8d6a"
+        "343d96559d5499e4c6e1215499bf245ff1bff9108537b88e9eff8745"
+    );
+
+    impl_tests!(XSalsa20Poly1305Siv, EMPTY, NO_CONTEXT, WITH_CONTEXT);
+}
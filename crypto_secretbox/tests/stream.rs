@@ -0,0 +1,116 @@
+//! STREAM tests.
+
+#![cfg(all(feature = "salsa20", feature = "std", feature = "stream"))]
+
+use crypto_secretbox::{
+    aead::{consts::U19, generic_array::GenericArray, KeyInit},
+    stream::{EncryptorBE32, StreamReader, StreamWriter},
+    XSalsa20Poly1305,
+};
+use hex_literal::hex;
+use std::io::{Read, Write};
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const NONCE: &[u8; 19] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0");
+const CHUNK_SIZE: usize = 64;
+const SEGMENT_SIZE: usize = CHUNK_SIZE + 16;
+
+fn cipher() -> XSalsa20Poly1305 {
+    XSalsa20Poly1305::new(KEY.into())
+}
+
+fn nonce() -> &'static GenericArray<u8, U19> {
+    NONCE.into()
+}
+
+fn plaintext(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+    let mut writer = StreamWriter::new(cipher(), nonce(), CHUNK_SIZE, Vec::new());
+
+    // Write in odd-sized pieces to exercise segment buffering
+    for piece in plaintext.chunks(7) {
+        writer.write_all(piece).unwrap();
+    }
+
+    writer.finish().unwrap()
+}
+
+fn decrypt(ciphertext: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut reader = StreamReader::new(cipher(), nonce(), CHUNK_SIZE, ciphertext);
+    let mut plaintext = Vec::new();
+    reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
+
+#[test]
+fn round_trip() {
+    for len in [
+        0,
+        1,
+        CHUNK_SIZE - 1,
+        CHUNK_SIZE,
+        CHUNK_SIZE + 1,
+        5 * CHUNK_SIZE + 3,
+    ] {
+        let plaintext = plaintext(len);
+        let ciphertext = encrypt(&plaintext);
+
+        assert_eq!(ciphertext.len(), (len / CHUNK_SIZE + 1) * 16 + len);
+        assert_eq!(decrypt(&ciphertext).unwrap(), plaintext);
+    }
+}
+
+#[test]
+fn segments_match_encryptor() {
+    let plaintext = plaintext(CHUNK_SIZE + 5);
+    let ciphertext = encrypt(&plaintext);
+
+    let mut encryptor = EncryptorBE32::from_aead(cipher(), nonce());
+    let mut expected = encryptor.encrypt_next(&plaintext[..CHUNK_SIZE]).unwrap();
+    expected.extend(encryptor.encrypt_last(&plaintext[CHUNK_SIZE..]).unwrap());
+    assert_eq!(ciphertext, expected);
+}
+
+#[test]
+fn truncated() {
+    let ciphertext = encrypt(&plaintext(3 * CHUNK_SIZE));
+
+    // Drop the (empty) last segment
+    assert!(decrypt(&ciphertext[..3 * SEGMENT_SIZE]).is_err());
+
+    // Drop part of the last segment
+    assert!(decrypt(&ciphertext[..ciphertext.len() - 1]).is_err());
+
+    // Drop everything
+    assert!(decrypt(&[]).is_err());
+}
+
+#[test]
+fn reordered() {
+    let mut ciphertext = encrypt(&plaintext(3 * CHUNK_SIZE));
+    let (first, rest) = ciphertext.split_at_mut(SEGMENT_SIZE);
+    first.swap_with_slice(&mut rest[..SEGMENT_SIZE]);
+    assert!(decrypt(&ciphertext).is_err());
+}
+
+#[test]
+fn trailing_data() {
+    let mut ciphertext = encrypt(&plaintext(CHUNK_SIZE + 1));
+    ciphertext.push(0);
+    assert!(decrypt(&ciphertext).is_err());
+}
+
+#[test]
+fn modified() {
+    let mut ciphertext = encrypt(&plaintext(2 * CHUNK_SIZE));
+    ciphertext[SEGMENT_SIZE + 20] ^= 0xaa;
+
+    let mut reader = StreamReader::new(cipher(), nonce(), CHUNK_SIZE, &ciphertext[..]);
+    let mut buf = [0u8; CHUNK_SIZE];
+    reader.read_exact(&mut buf).unwrap();
+    assert!(reader.read(&mut buf).is_err());
+    assert!(reader.read(&mut buf).is_err());
+}
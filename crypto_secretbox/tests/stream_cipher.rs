@@ -0,0 +1,151 @@
+//! `crypto_stream` tests.
+//!
+//! Test vectors generated using `test-vector-gen` which uses a libsodium
+//! reference.
+
+#![cfg(any(feature = "chacha20", feature = "salsa20"))]
+
+use hex_literal::hex;
+
+const KEY: &[u8; 32] = &hex!("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+const NONCE: &[u8; 24] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+const MESSAGE: &[u8] =
+    b"crypto_stream xor_ic test message, which spans more than one 64-byte block";
+
+macro_rules! impl_tests {
+    ($keystream:path, $xor:path, $xor_ic:path, $ic:expr, $keystream_kat:expr, $xor_ic_kat:expr) => {
+        #[test]
+        fn keystream() {
+            let mut buffer = [0xffu8; 80];
+            $keystream(KEY.into(), NONCE.into(), &mut buffer);
+            assert_eq!($keystream_kat, buffer);
+        }
+
+        #[test]
+        fn xor() {
+            let mut buffer = MESSAGE.to_vec();
+            $xor(KEY.into(), NONCE.into(), &mut buffer);
+
+            for ((ct, pt), ks) in buffer.iter().zip(MESSAGE).zip($keystream_kat) {
+                assert_eq!(*ct, pt ^ ks);
+            }
+
+            $xor(KEY.into(), NONCE.into(), &mut buffer);
+            assert_eq!(MESSAGE, buffer);
+        }
+
+        #[test]
+        fn xor_ic() {
+            let mut buffer = MESSAGE.to_vec();
+            $xor_ic(KEY.into(), NONCE.into(), $ic, &mut buffer).unwrap();
+            assert_eq!($xor_ic_kat, buffer);
+
+            $xor_ic(KEY.into(), NONCE.into(), $ic, &mut buffer).unwrap();
+            assert_eq!(MESSAGE, buffer);
+        }
+
+        #[test]
+        fn xor_ic_offset() {
+            // Starting at block 1 matches the keystream from byte 64 onwards
+            let mut buffer = [0u8; 16];
+            $xor_ic(KEY.into(), NONCE.into(), 1, &mut buffer).unwrap();
+            assert_eq!($keystream_kat[64..], buffer);
+        }
+    };
+}
+
+#[cfg(feature = "chacha20")]
+mod xchacha20 {
+    use super::{KEY, MESSAGE, NONCE};
+    use crypto_secretbox::stream_cipher::{xchacha20, xchacha20_xor, xchacha20_xor_ic};
+    use hex_literal::hex;
+
+    const KEYSTREAM: &[u8] = &hex!(
+        "4febf2fe4b359c508dc5e8b5980c88e38946d8f18f313465c862a08782648248018dacdcb9041788"
+        "53a46dca3a0eaaee747cba97434eaffad58fea8222047e0de6c3a6775106e0331ad714d2f27a5564"
+    );
+
+    /// The last two usable blocks of the 32-bit block counter.
+    const IC: u64 = 0x0000_0000_ffff_fffd;
+
+    const XOR_IC_CIPHERTEXT: &[u8] = &hex!(
+        "29778854e66518ee087684f49d04b137d8b921425d4aa96ded7f7d6d064879bb48b621832c3ae2e5"
+        "a228aa18777368d5e6b069cd1877a834042b93dc6cc97bd72c27e129c5cf4328f054"
+    );
+
+    impl_tests!(
+        xchacha20,
+        xchacha20_xor,
+        xchacha20_xor_ic,
+        IC,
+        KEYSTREAM,
+        XOR_IC_CIPHERTEXT
+    );
+
+    /// Crosses from the low to the high word of the 64-bit block counter.
+    const CARRY_IC: u64 = 0x0000_0000_ffff_ffff;
+
+    const XOR_IC_CARRY_CIPHERTEXT: &[u8] = &hex!(
+        "866858c6de12b51b66f26036d23c90c688c53110b97d49dc9de986b4291ce2914e8634ff440d471c"
+        "695e96d4b8eb7e3471ca40f97220ee7ffd95dabd39470bbcdb9d3ace3b066835f842"
+    );
+
+    #[test]
+    fn xor_ic_counter_carry() {
+        let mut buffer = MESSAGE.to_vec();
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), CARRY_IC, &mut buffer).unwrap();
+        assert_eq!(XOR_IC_CARRY_CIPHERTEXT, buffer);
+
+        // Starting on either side of the carry
+        let mut buffer = MESSAGE.to_vec();
+        let (first, second) = buffer.split_at_mut(64);
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), CARRY_IC, first).unwrap();
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), CARRY_IC + 1, second).unwrap();
+        assert_eq!(XOR_IC_CARRY_CIPHERTEXT, buffer);
+
+        // Consecutive blocks in the middle of the counter range
+        let mut buffer = MESSAGE.to_vec();
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), IC, &mut buffer).unwrap();
+        let mut expected = MESSAGE.to_vec();
+        let (first, second) = expected.split_at_mut(64);
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), IC, first).unwrap();
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), IC + 1, second).unwrap();
+        assert_eq!(expected, buffer);
+    }
+
+    #[test]
+    fn xor_ic_counter_exhausted() {
+        let mut buffer = MESSAGE.to_vec();
+        assert!(xchacha20_xor_ic(KEY.into(), NONCE.into(), u64::MAX, &mut buffer).is_err());
+        xchacha20_xor_ic(KEY.into(), NONCE.into(), u64::MAX, &mut buffer[..64]).unwrap();
+    }
+}
+
+#[cfg(feature = "salsa20")]
+mod xsalsa20 {
+    use super::{KEY, MESSAGE, NONCE};
+    use crypto_secretbox::stream_cipher::{xsalsa20, xsalsa20_xor, xsalsa20_xor_ic};
+    use hex_literal::hex;
+
+    const KEYSTREAM: &[u8] = &hex!(
+        "eea6a7251c1e72916d11c2cb214d3c252539121d8e234e652d651fa4c8cff880309e645a74e9e0a6"
+        "0d8243acd9177ab51a1beb8d5a2f5d700c093c5e5585579625337bd3ab619d615760d8c5b224a85b"
+    );
+
+    const XOR_IC_CIPHERTEXT: &[u8] = &hex!(
+        "4f32c430e79523373f2b422161e81fd2804e9cd8538f18d34d2aad4e1481a0f69832a6cce3899e72"
+        "4b48ba692d20db9b4778b6a0d023c579326c21b47b361126cedc7463e98b3f5efdec"
+    );
+
+    /// Initial block counter which doesn't fit in 32 bits.
+    const IC: u64 = 0x0000_0001_ffff_ffff;
+
+    impl_tests!(
+        xsalsa20,
+        xsalsa20_xor,
+        xsalsa20_xor_ic,
+        IC,
+        KEYSTREAM,
+        XOR_IC_CIPHERTEXT
+    );
+}
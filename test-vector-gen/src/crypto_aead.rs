@@ -0,0 +1,71 @@
+//! Test vector generator for the `crypto_aead_chacha20poly1305_ietf` and
+//! `crypto_aead_xchacha20poly1305_ietf` AEAD ciphers.
+
+const KEY: &[u8; 32] = &[
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x8d, 0x8e, 0x8f,
+    0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0x9b, 0x9c, 0x9d, 0x9e, 0x9f,
+];
+
+const NONCE: &[u8; 24] = &[
+    0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+    0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57,
+];
+
+const AAD: &[u8] = &[
+    0x50, 0x51, 0x52, 0x53, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7,
+];
+
+const MESSAGE: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+pub fn generate() {
+    generate_chacha20poly1305_ietf();
+    generate_xchacha20poly1305_ietf();
+}
+
+fn generate_chacha20poly1305_ietf() {
+    let mut ct = vec![0u8; MESSAGE.len() + 16];
+    let mut ct_len = 0u64;
+    let ret = unsafe {
+        libsodium_sys::crypto_aead_chacha20poly1305_ietf_encrypt(
+            ct.as_mut_ptr(),
+            &mut ct_len,
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            AAD.as_ptr(),
+            AAD.len() as u64,
+            core::ptr::null(),
+            NONCE.as_ptr(),
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    assert_eq!(ct_len as usize, ct.len());
+    println!(
+        "CHACHA20POLY1305_IETF_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+}
+
+fn generate_xchacha20poly1305_ietf() {
+    let mut ct = vec![0u8; MESSAGE.len() + 16];
+    let mut ct_len = 0u64;
+    let ret = unsafe {
+        libsodium_sys::crypto_aead_xchacha20poly1305_ietf_encrypt(
+            ct.as_mut_ptr(),
+            &mut ct_len,
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            AAD.as_ptr(),
+            AAD.len() as u64,
+            core::ptr::null(),
+            NONCE.as_ptr(),
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    assert_eq!(ct_len as usize, ct.len());
+    println!(
+        "XCHACHA20POLY1305_IETF_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+}
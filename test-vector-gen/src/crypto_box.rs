@@ -11,8 +11,8 @@ const ALICE_PUBLIC_KEY: [u8; 32] =
 // Bob's keypair
 const BOB_SECRET_KEY: [u8; 32] =
     hex!("b581fb5ae182a16f603f39270d4e3b95bc008310b727a11dd4e784a0044d461b");
-// const BOB_PUBLIC_KEY: [u8; 32] =
-//     hex!("e8980c86e032f1eb2975052e8d65bddd15c3b59641174ec9678a53789d92c754");
+const BOB_PUBLIC_KEY: [u8; 32] =
+    hex!("e8980c86e032f1eb2975052e8d65bddd15c3b59641174ec9678a53789d92c754");
 
 const NONCE: &[u8; 24] = &hex!("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
 
@@ -30,8 +30,66 @@ const PLAINTEXT: &[u8] = &[
 
 const BOXZEROBYTES: usize = 16;
 
+const SEALBYTES: usize = 32 + 16;
+
 pub fn generate() {
+    generate_beforenm();
+    generate_seed_keypair();
     generate_xchacha20poly1305();
+    generate_xchacha20poly1305_seal();
+}
+
+fn generate_beforenm() {
+    let mut k = [0u8; 32];
+
+    let ret = unsafe {
+        libsodium_sys::crypto_box_beforenm(
+            k.as_mut_ptr(),
+            ALICE_PUBLIC_KEY.as_ptr(),
+            BOB_SECRET_KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "SALSA20_SHARED_KEY: [u8; 32] = hex!(\"{}\");",
+        hex::encode(k)
+    );
+
+    let ret = unsafe {
+        libsodium_sys::crypto_box_curve25519xchacha20poly1305_beforenm(
+            k.as_mut_ptr(),
+            ALICE_PUBLIC_KEY.as_ptr(),
+            BOB_SECRET_KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "CHACHA20_SHARED_KEY: [u8; 32] = hex!(\"{}\");",
+        hex::encode(k)
+    );
+}
+
+fn generate_seed_keypair() {
+    let mut seed = [0u8; 32];
+    for (i, byte) in seed.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+
+    for seed in [[0u8; 32], seed] {
+        let mut pk = [0u8; 32];
+        let mut sk = [0u8; 32];
+
+        let ret = unsafe {
+            libsodium_sys::crypto_box_seed_keypair(pk.as_mut_ptr(), sk.as_mut_ptr(), seed.as_ptr())
+        };
+        assert_eq!(ret, 0);
+        println!(
+            "SEED: {}\nSEED_SECRET_KEY: {}\nSEED_PUBLIC_KEY: {}",
+            hex::encode(seed),
+            hex::encode(sk),
+            hex::encode(pk)
+        );
+    }
 }
 
 fn generate_xchacha20poly1305() {
@@ -53,3 +111,21 @@ fn generate_xchacha20poly1305() {
         hex::encode(ct)
     );
 }
+
+fn generate_xchacha20poly1305_seal() {
+    let mut ct = [0u8; SEALBYTES + PLAINTEXT.len()];
+
+    let ret = unsafe {
+        libsodium_sys::crypto_box_curve25519xchacha20poly1305_seal(
+            ct.as_mut_ptr(),
+            PLAINTEXT.as_ptr(),
+            PLAINTEXT.len() as u64,
+            BOB_PUBLIC_KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "CHACHA20POLY1305_SEAL_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+}
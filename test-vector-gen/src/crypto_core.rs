@@ -0,0 +1,58 @@
+//! Test vector generator for the `crypto_core` HSalsa20 and HChaCha20
+//! functions.
+
+const KEY: &[u8; 32] = &[
+    0x1b, 0x27, 0x55, 0x64, 0x73, 0xe9, 0x85, 0xd4, 0x62, 0xcd, 0x51, 0x19, 0x7a, 0x9a, 0x46, 0xc7,
+    0x60, 0x09, 0x54, 0x9e, 0xac, 0x64, 0x74, 0xf2, 0x06, 0xc4, 0xee, 0x08, 0x44, 0xf6, 0x83, 0x89,
+];
+
+const INPUT: &[u8; 16] = &[
+    0x69, 0x69, 0x6e, 0xe9, 0x55, 0xb6, 0x2b, 0x73, 0xcd, 0x62, 0xbd, 0xa8, 0x75, 0xfc, 0x73, 0xd6,
+];
+
+const CONSTANT: &[u8; 16] = b"custom constant!";
+
+pub fn generate() {
+    generate_hsalsa20();
+    generate_hchacha20();
+}
+
+fn generate_hsalsa20() {
+    for (name, constant) in [("DEFAULT", None), ("CUSTOM", Some(CONSTANT))] {
+        let mut out = [0u8; 32];
+        let ret = unsafe {
+            libsodium_sys::crypto_core_hsalsa20(
+                out.as_mut_ptr(),
+                INPUT.as_ptr(),
+                KEY.as_ptr(),
+                constant.map_or(core::ptr::null(), |c| c.as_ptr()),
+            )
+        };
+        assert_eq!(ret, 0);
+        println!(
+            "HSALSA20_{}_CONSTANT_OUTPUT: &[u8; 32] = &hex!(\"{}\");",
+            name,
+            hex::encode(out)
+        );
+    }
+}
+
+fn generate_hchacha20() {
+    for (name, constant) in [("DEFAULT", None), ("CUSTOM", Some(CONSTANT))] {
+        let mut out = [0u8; 32];
+        let ret = unsafe {
+            libsodium_sys::crypto_core_hchacha20(
+                out.as_mut_ptr(),
+                INPUT.as_ptr(),
+                KEY.as_ptr(),
+                constant.map_or(core::ptr::null(), |c| c.as_ptr()),
+            )
+        };
+        assert_eq!(ret, 0);
+        println!(
+            "HCHACHA20_{}_CONSTANT_OUTPUT: &[u8; 32] = &hex!(\"{}\");",
+            name,
+            hex::encode(out)
+        );
+    }
+}
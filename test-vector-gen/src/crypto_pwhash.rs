@@ -0,0 +1,59 @@
+//! Test vector generator for password-based `crypto_secretbox` encryption,
+//! i.e. `crypto_pwhash` followed by `crypto_secretbox_easy`.
+
+const PASSWORD: &[u8] = b"correct horse battery staple";
+
+const SALT: &[u8; 16] = &[
+    0x5c, 0x1d, 0x7e, 0x20, 0x9a, 0x43, 0xf1, 0x0b, 0x6d, 0x88, 0x2e, 0xc4, 0x39, 0xa7, 0x52, 0xee,
+];
+
+const NONCE: &[u8; 24] = &[
+    0x69, 0x69, 0x6e, 0xe9, 0x55, 0xb6, 0x2b, 0x73, 0xcd, 0x62, 0xbd, 0xa8, 0x75, 0xfc, 0x73, 0xd6,
+    0x82, 0x19, 0xe0, 0x03, 0x6b, 0x7a, 0x0b, 0x37,
+];
+
+const MESSAGE: &[u8] = b"backup encrypted with a passphrase";
+
+/// Cost parameters and the prefix of the printed constant names.
+const PARAMS: &[(u64, usize, &str)] = &[
+    (3, 1024 * 1024, "PWHASH"),
+    // More passes than any preset, but little memory
+    (10, 64 * 1024, "PWHASH_HIGH_OPS"),
+];
+
+pub fn generate() {
+    for &(ops_limit, mem_limit, name) in PARAMS {
+        generate_vector(ops_limit, mem_limit, name);
+    }
+}
+
+fn generate_vector(ops_limit: u64, mem_limit: usize, name: &str) {
+    let mut key = [0u8; 32];
+    let ret = unsafe {
+        libsodium_sys::crypto_pwhash(
+            key.as_mut_ptr(),
+            key.len() as u64,
+            PASSWORD.as_ptr() as *const _,
+            PASSWORD.len() as u64,
+            SALT.as_ptr(),
+            ops_limit,
+            mem_limit,
+            libsodium_sys::crypto_pwhash_ALG_ARGON2ID13 as i32,
+        )
+    };
+    assert_eq!(ret, 0);
+    println!("{name}_KEY: &[u8; 32] = &hex!(\"{}\");", hex::encode(key));
+
+    let mut ct = vec![0u8; MESSAGE.len() + 16];
+    let ret = unsafe {
+        libsodium_sys::crypto_secretbox_easy(
+            ct.as_mut_ptr(),
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            NONCE.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!("{name}_CIPHERTEXT: &[u8] = &hex!(\"{}\");", hex::encode(ct));
+}
@@ -0,0 +1,113 @@
+//! Test vector generator for the `crypto_stream` extended-nonce stream ciphers.
+
+const KEY: &[u8; 32] = &[
+    0x1b, 0x27, 0x55, 0x64, 0x73, 0xe9, 0x85, 0xd4, 0x62, 0xcd, 0x51, 0x19, 0x7a, 0x9a, 0x46, 0xc7,
+    0x60, 0x09, 0x54, 0x9e, 0xac, 0x64, 0x74, 0xf2, 0x06, 0xc4, 0xee, 0x08, 0x44, 0xf6, 0x83, 0x89,
+];
+
+const NONCE: &[u8; 24] = &[
+    0x69, 0x69, 0x6e, 0xe9, 0x55, 0xb6, 0x2b, 0x73, 0xcd, 0x62, 0xbd, 0xa8, 0x75, 0xfc, 0x73, 0xd6,
+    0x82, 0x19, 0xe0, 0x03, 0x6b, 0x7a, 0x0b, 0x37,
+];
+
+const MESSAGE: &[u8] =
+    b"crypto_stream xor_ic test message, which spans more than one 64-byte block";
+
+const KEYSTREAM_LEN: usize = 80;
+
+const XSALSA20_IC: u64 = 0x0000_0001_ffff_ffff;
+
+const XCHACHA20_IC: u64 = 0x0000_0000_ffff_fffd;
+
+/// Crosses from the low to the high word of the 64-bit block counter.
+const XCHACHA20_CARRY_IC: u64 = 0x0000_0000_ffff_ffff;
+
+pub fn generate() {
+    generate_xsalsa20();
+    generate_xchacha20();
+}
+
+fn generate_xsalsa20() {
+    let mut keystream = [0u8; KEYSTREAM_LEN];
+    let ret = unsafe {
+        libsodium_sys::crypto_stream_xsalsa20(
+            keystream.as_mut_ptr(),
+            keystream.len() as u64,
+            NONCE.as_ptr(),
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "XSALSA20_KEYSTREAM: &[u8] = &hex!(\"{}\");",
+        hex::encode(keystream)
+    );
+
+    let mut ct = vec![0u8; MESSAGE.len()];
+    let ret = unsafe {
+        libsodium_sys::crypto_stream_xsalsa20_xor_ic(
+            ct.as_mut_ptr(),
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            NONCE.as_ptr(),
+            XSALSA20_IC,
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "XSALSA20_XOR_IC_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+}
+
+fn generate_xchacha20() {
+    let mut keystream = [0u8; KEYSTREAM_LEN];
+    let ret = unsafe {
+        libsodium_sys::crypto_stream_xchacha20(
+            keystream.as_mut_ptr(),
+            keystream.len() as u64,
+            NONCE.as_ptr(),
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "XCHACHA20_KEYSTREAM: &[u8] = &hex!(\"{}\");",
+        hex::encode(keystream)
+    );
+
+    let mut ct = vec![0u8; MESSAGE.len()];
+    let ret = unsafe {
+        libsodium_sys::crypto_stream_xchacha20_xor_ic(
+            ct.as_mut_ptr(),
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            NONCE.as_ptr(),
+            XCHACHA20_IC,
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "XCHACHA20_XOR_IC_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+
+    let mut ct = vec![0u8; MESSAGE.len()];
+    let ret = unsafe {
+        libsodium_sys::crypto_stream_xchacha20_xor_ic(
+            ct.as_mut_ptr(),
+            MESSAGE.as_ptr(),
+            MESSAGE.len() as u64,
+            NONCE.as_ptr(),
+            XCHACHA20_CARRY_IC,
+            KEY.as_ptr(),
+        )
+    };
+    assert_eq!(ret, 0);
+    println!(
+        "XCHACHA20_XOR_IC_CARRY_CIPHERTEXT: &[u8] = &hex!(\"{}\");",
+        hex::encode(ct)
+    );
+}
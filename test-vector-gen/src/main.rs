@@ -1,7 +1,15 @@
+mod crypto_aead;
 mod crypto_box;
+mod crypto_core;
+mod crypto_pwhash;
 mod crypto_secretbox;
+mod crypto_stream;
 
 fn main() {
+    crypto_aead::generate();
     crypto_box::generate();
+    crypto_core::generate();
+    crypto_pwhash::generate();
     crypto_secretbox::generate();
+    crypto_stream::generate();
 }